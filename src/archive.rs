@@ -0,0 +1,144 @@
+use crate::{dir::EzDir, error::EzError};
+use std::{io, fs, ops::{Deref, DerefMut}, path::{Path, PathBuf}, sync::atomic::{AtomicUsize, Ordering}};
+
+static EXTRACTED: AtomicUsize = AtomicUsize::new(0);
+
+///Largest total uncompressed size [`EzDir::open_archive`] will extract, 1 GiB.
+pub const ARCHIVE_MAX_BYTES: u64 = 1 << 30;
+
+impl EzDir {
+    ///Opens a zip archive as a fully walked, read-only directory tree, so code written against
+    ///[`EzDir`] works the same on `project/` and `project.zip`. Gives up on archives that
+    ///unpack to more than [`ARCHIVE_MAX_BYTES`], see [`EzDir::open_archive_limited`].
+    ///
+    ///The archive is extracted to a new private directory under [`std::env::temp_dir`] and every
+    ///file in it is marked read-only. Paths in the tree point into that directory, which is
+    ///deleted again when the returned [`ArchiveDir`] is dropped. The tree is a snapshot: later
+    ///changes to the archive aren't seen.
+    ///# Errors
+    ///This function will error if the archive can't be read, is malformed, or can't be
+    ///extracted.
+    pub fn open_archive(path: impl AsRef<Path>) -> Result<ArchiveDir, EzError> {
+        Self::open_archive_limited(path, ARCHIVE_MAX_BYTES)
+    }
+
+    ///Like [`EzDir::open_archive`], but gives up once more than `max_bytes` have been extracted,
+    ///which protects against archives that unpack to far more than their own size. The limit
+    ///counts the bytes actually written, not the sizes the archive claims.
+    ///# Errors
+    ///This function will error if the archive can't be read, is malformed, contains entries
+    ///that would land outside the extracted directory or symlinks, unpacks to more than
+    ///`max_bytes`, or can't be extracted.
+    pub fn open_archive_limited(path: impl AsRef<Path>, max_bytes: u64) -> Result<ArchiveDir, EzError> {
+        let source = path.as_ref().to_owned();
+        let mut archive = zip::ZipArchive::new(fs::File::open(&source)?).map_err(io::Error::from)?;
+        let extracted = Extracted::create()?;
+
+        let mut left = max_bytes;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(io::Error::from)?;
+            let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{msg}: {}", entry.name()));
+            if entry.is_symlink() {
+                return Err(invalid("Symlinks in archives aren't supported").into())
+            }
+            let Some(name) = entry.enclosed_name() else {
+                return Err(invalid("Archive entry escapes the extracted directory").into())
+            };
+            let dest = extracted.0.join(name);
+            if entry.is_dir() {
+                fs::create_dir_all(dest)?;
+                continue
+            }
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            //read one byte past what's left so going over is noticed
+            let mut out = fs::File::create_new(&dest)?;
+            let written = io::copy(&mut io::Read::take(&mut entry, left.saturating_add(1)), &mut out)?;
+            if written > left {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Archive unpacks to more than {max_bytes} bytes")).into())
+            }
+            left -= written;
+        }
+
+        let mut dir = EzDir::new(&extracted.0, false)?;
+        dir.walk(0);
+        for file in dir.iter_files() {
+            let mut perms = file.permissions();
+            perms.set_readonly(true);
+            fs::set_permissions(file.path(), perms)?;
+        }
+        //walk again so the cached metadata has the new permissions
+        dir.walk(0);
+        Ok(ArchiveDir { dir, source, _extracted: extracted })
+    }
+}
+
+///Directory tree extracted from an archive by [`EzDir::open_archive`]. Derefs to the walked
+///[`EzDir`], and deletes the extracted files when dropped. Requires the `archive` feature.
+#[derive(Debug)]
+pub struct ArchiveDir {
+    //dropped before `_extracted`, so every handle is closed by the time it's deleted
+    dir: EzDir,
+    source: PathBuf,
+    _extracted: Extracted,
+}
+impl ArchiveDir {
+    ///Returns the path of the archive this was extracted from.
+    #[must_use] pub fn source(&self) -> &Path {
+        &self.source
+    }
+}
+impl Deref for ArchiveDir {
+    type Target = EzDir;
+
+    fn deref(&self) -> &Self::Target {
+        &self.dir
+    }
+}
+impl DerefMut for ArchiveDir {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.dir
+    }
+}
+
+//private temporary directory, removed along with its contents on drop
+#[derive(Debug)]
+struct Extracted(PathBuf);
+impl Extracted {
+    fn create() -> io::Result<Self> {
+        loop {
+            let dest = std::env::temp_dir().join(format!(
+                "ez_fs_archive_{}_{}",
+                std::process::id(),
+                EXTRACTED.fetch_add(1, Ordering::Relaxed),
+            ));
+            //never reuse a directory that's already there, someone else may own it
+            let mut builder = fs::DirBuilder::new();
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            match builder.create(&dest) {
+                Ok(()) => return Ok(Self(dest)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {},
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+impl Drop for Extracted {
+    fn drop(&mut self) {
+        //Windows won't delete read-only files, so make them writable again first
+        #[cfg(windows)]
+        #[allow(clippy::permissions_set_readonly_false)]
+        if let Ok(mut dir) = EzDir::new(&self.0, false) {
+            dir.walk(0);
+            for file in dir.flatten() {
+                let mut perms = file.permissions();
+                perms.set_readonly(false);
+                let _ = fs::set_permissions(file.path(), perms);
+            }
+        }
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{dir::EzDir, error::EzError};
+use std::{io, fs, path::{Component, Path, PathBuf}, sync::{Arc, Mutex, MutexGuard}, time::SystemTime, collections::HashMap};
+
+///Wraps an [`EzDir`] with an in-memory cache of file contents, for small files that are read
+///over and over, like templates or config. The cache holds at most `max_bytes` of contents,
+///evicting the least recently used files first, and rereads any file whose size or
+///modification time changed since it was cached.
+///```
+///use ez_fs::{CachedEzDir, EzDir};
+///
+///let dir = CachedEzDir::new(EzDir::new(".", false).unwrap(), 64 * 1024);
+///let first = dir.read_cached("Cargo.toml").unwrap();
+/////served from memory
+///let second = dir.read_cached("Cargo.toml").unwrap();
+///assert_eq!(first, second);
+///```
+#[derive(Debug)]
+pub struct CachedEzDir {
+    dir: EzDir,
+    max_bytes: usize,
+    lru: Mutex<Lru>,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    entries: HashMap<PathBuf, Cached>,
+    size: usize,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct Cached {
+    data: Arc<[u8]>,
+    stamp: (u64, Option<SystemTime>),
+    used: u64,
+}
+
+impl CachedEzDir {
+    ///Wraps `dir` with a content cache holding at most `max_bytes`.
+    #[must_use] pub fn new(dir: EzDir, max_bytes: usize) -> Self {
+        Self { dir, max_bytes, lru: Mutex::default() }
+    }
+
+    ///Returns the wrapped directory.
+    #[must_use] pub fn dir(&self) -> &EzDir {
+        &self.dir
+    }
+
+    ///Unwraps the directory, dropping the cache.
+    #[must_use] pub fn into_inner(self) -> EzDir {
+        self.dir
+    }
+
+    ///Returns the contents of the file at `relative_path` inside the directory, from memory if
+    ///it's cached and unchanged. Files larger than the whole cache are read but never cached.
+    ///The cache isn't locked while reading from disk, so a slow read doesn't hold up others.
+    ///# Errors
+    ///This function will error if the file can't be read, or with
+    ///[`io::ErrorKind::InvalidInput`] if `relative_path` is absolute or contains `..`, since
+    ///that could point outside the directory.
+    pub fn read_cached(&self, relative_path: impl AsRef<Path>) -> Result<Arc<[u8]>, EzError> {
+        let relative_path = relative_path.as_ref();
+        if !relative_path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a relative path inside the directory", relative_path.display()),
+            ).into())
+        }
+        let path = self.dir.path().join(relative_path);
+        let meta = fs::metadata(&path)?;
+        let stamp = (meta.len(), meta.modified().ok());
+
+        {
+            let mut lru = self.lock();
+            lru.tick += 1;
+            let tick = lru.tick;
+            if let Some(cached) = lru.entries.get_mut(&path) {
+                if cached.stamp == stamp {
+                    cached.used = tick;
+                    return Ok(Arc::clone(&cached.data))
+                }
+            }
+        }
+
+        let data: Arc<[u8]> = fs::read(&path)?.into();
+        if data.len() > self.max_bytes {
+            return Ok(data)
+        }
+
+        let mut lru = self.lock();
+        //drop the stale copy, or one another thread cached while this one was reading
+        if let Some(old) = lru.entries.remove(&path) {
+            lru.size -= old.data.len();
+        }
+        while lru.size + data.len() > self.max_bytes {
+            let Some(oldest) = lru.entries.iter().min_by_key(|(_, c)| c.used).map(|(p, _)| p.clone()) else { break };
+            if let Some(evicted) = lru.entries.remove(&oldest) {
+                lru.size -= evicted.data.len();
+            }
+        }
+        lru.tick += 1;
+        let used = lru.tick;
+        lru.size += data.len();
+        lru.entries.insert(path, Cached { data: Arc::clone(&data), stamp, used });
+        Ok(data)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru> {
+        self.lru.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
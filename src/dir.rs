@@ -1,20 +1,35 @@
-use crate::file::EzFile;
-use std::{io, fs, path::Path, fmt::Display};
-
-macro_rules! io_err {
-    ($err:tt) => {
-        io::Error::new(io::ErrorKind::Other, $err)   
-    };
-}
+use crate::{file::EzFile, glob, error::EzError};
+use std::{io, fs, path::{Path, PathBuf}, fmt::Display, time::SystemTime, collections::{BTreeSet, HashMap}};
 
 ///Representation of a directory.
 ///Directories are lazily evaluated, and will not be scanned until asked to.
+///
+///Two directories are equal if they have the same path, whatever has been cached.
+///
+///Directories are [`Send`] and [`Sync`], like [`EzFile`] and [`EzEntry`], so trees can be
+///walked, or files handed out, across threads.
 #[derive(Debug)]
 pub struct EzDir {
-    path: String,
-    entries: Option<Vec<EzEntry>>
+    path: PathBuf,
+    entries: Option<Vec<EzEntry>>,
+    total: Option<usize>,
+    ignore_hidden: bool,
+    //every indexed path with file sizes and mtimes from a loaded index, see `EzDir::revalidate`
+    #[cfg(feature = "serde")]
+    pub(crate) index: Option<std::collections::HashMap<PathBuf, crate::index::Stamp>>,
 }
 impl EzDir {
+    ///Returns `true` if `path` exists and is a directory, following symlinks.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///assert!(EzDir::exists("src"));
+    ///assert!(!EzDir::exists("Cargo.toml"));
+    ///```
+    #[must_use] pub fn exists(path: impl AsRef<Path>) -> bool {
+        path.as_ref().is_dir()
+    }
+
     ///Constructs a new directory from a given path.
     ///Directories are lazy, so pass `cache` as `true` to fill the directory.
     ///This is so that subdirectories are not walked immediately.
@@ -26,23 +41,96 @@ impl EzDir {
     ///assert!(dir.is_cached())
     ///```
     ///# Errors
-    ///This function will error if path does not exist.
-    pub fn new(path: &str, cache: bool) -> io::Result<Self> {
-        let dir = Path::new(path);
-        if dir.is_dir() {
+    ///This function will error with [`EzError::NotADirectory`] if `path` isn't a directory, or
+    ///[`EzError::Io`] if it does not exist or can't be read.
+    pub fn new(path: impl AsRef<Path>, cache: bool) -> Result<Self, EzError> {
+        let path = path.as_ref();
+        if fs::metadata(path)?.is_dir() {
             if cache {
-                let dir = fs::read_dir(path)?
-                    .filter_map(|e| e.and_then(EzEntry::try_from).ok())
-                    .collect();
-                Ok(Self {path: path.to_owned(), entries: Some(dir)}) 
+                Ok(Self::from_parts(path.to_owned(), Some(scan(path, false)?)))
             } else {
-                Ok(Self {path: path.to_owned(), entries: None}) 
+                Ok(Self::from_parts(path.to_owned(), None))
             }
         } else {
-            Err(io_err!("Path is not a directory"))
+            Err(EzError::NotADirectory(path.to_owned()))
         }
     }
 
+    ///Like [`EzDir::new`], but resolves `path` against the current working directory right away
+    ///and stores the absolute path. A directory from [`EzDir::new`] keeps its path as given, so
+    ///a relative one is looked up again on every scan and breaks if the working directory
+    ///changes in the meantime. Symlinks and `..` are left as they are.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let dir = EzDir::new_absolute("src", false).unwrap();
+    ///assert!(dir.path().is_absolute());
+    ///```
+    ///# Errors
+    ///This function will error if path does not exist or the working directory can't be read.
+    pub fn new_absolute(path: impl AsRef<Path>, cache: bool) -> Result<Self, EzError> {
+        Self::new(std::env::current_dir()?.join(path), cache)
+    }
+
+    ///Creates a new, empty directory at `path` and returns it already cached, so files can be
+    ///added to it right away. The parent has to exist, see [`EzDir::create_all`] otherwise.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let dir = EzDir::create("foo_created").unwrap();
+    ///assert_eq!(dir.len(), Some(0));
+    ///assert!(EzDir::create("foo_created").is_err());
+    ///# std::fs::remove_dir("foo_created").unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if `path` already exists or its parent doesn't.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, EzError> {
+        let path = path.as_ref();
+        fs::create_dir(path)?;
+        let mut dir = Self::from_parts(path.to_owned(), Some(vec![]));
+        dir.count_total();
+        Ok(dir)
+    }
+    ///Like [`EzDir::create`], but also creates any missing parents, and succeeds if the directory
+    ///already exists, caching whatever it holds.
+    ///# Errors
+    ///This function will error if `path` or one of its parents exists but isn't a directory, or
+    ///can't be created.
+    pub fn create_all(path: impl AsRef<Path>) -> Result<Self, EzError> {
+        let path = path.as_ref();
+        fs::create_dir_all(path)?;
+        Self::new(path, true)
+    }
+
+    ///Groups files from anywhere into one cached directory under a synthetic `path`, so the
+    ///iteration and flattening methods work on ad-hoc collections. Nothing is checked or created
+    ///on disk, and the files keep their own paths. Methods that rescan, like
+    ///[`EzDir::cache`] or [`EzDir::walk`], read the real `path` and replace the group.
+    ///```
+    ///use ez_fs::{EzDir, EzFile};
+    ///
+    ///let files = vec![EzFile::create("foo_group_a.txt").unwrap(), EzFile::create("foo_group_b.txt").unwrap()];
+    ///let group = EzDir::from_files("group", files);
+    ///assert_eq!(group.len(), Some(2));
+    ///```
+    pub fn from_files(path: impl AsRef<Path>, files: impl IntoIterator<Item = EzFile>) -> Self {
+        let entries = files.into_iter().map(EzEntry::File).collect();
+        let mut dir = Self::from_parts(path.as_ref().to_owned(), Some(entries));
+        dir.count_total();
+        dir
+    }
+
+    //builds a directory model without checking the disk
+    pub(crate) fn from_parts(path: PathBuf, entries: Option<Vec<EzEntry>>) -> Self {
+        Self {
+            path,
+            entries,
+            total: None,
+            ignore_hidden: false,
+            #[cfg(feature = "serde")]
+            index: None,
+        }
+    }
 
     ///Returns the path of this directory.
     ///```
@@ -50,20 +138,85 @@ impl EzDir {
     ///
     ///let dir = EzDir::new(".", false).unwrap();
     ///
-    ///assert_eq!(dir.path(), ".");
+    ///assert_eq!(dir.path(), std::path::Path::new("."));
     ///```
-    #[must_use] pub fn path(&self) -> &str {
-        self.path.as_ref()
+    #[must_use] pub fn path(&self) -> &Path {
+        &self.path
+    }
+    ///Returns the path of this directory as a `&str`, or [`None`] if it isn't valid UTF-8.
+    #[must_use] pub fn path_str(&self) -> Option<&str> {
+        self.path.to_str()
     }
     
+    ///Returns the parent directory, uncached, or [`None`] at the root of the filesystem. Relative
+    ///paths stay relative: the parent of `src` is `.`, and the parent of `.` is `..`. The hidden
+    ///entry setting carries over.
+    ///```
+    ///use ez_fs::EzDir;
+    ///use std::path::Path;
+    ///
+    ///let dir = EzDir::new("src", false).unwrap();
+    ///assert_eq!(dir.parent().unwrap().path(), Path::new("."));
+    ///```
+    #[must_use] pub fn parent(&self) -> Option<EzDir> {
+        use std::path::Component;
+
+        let path = match self.path.components().next_back() {
+            None | Some(Component::CurDir) => PathBuf::from(".."),
+            Some(Component::ParentDir) => self.path.join(".."),
+            Some(Component::Normal(_)) => match self.path.parent()? {
+                p if p.as_os_str().is_empty() => PathBuf::from("."),
+                p => p.to_owned(),
+            },
+            Some(Component::RootDir | Component::Prefix(_)) => return None,
+        };
+        let mut parent = Self::from_parts(path, None);
+        parent.ignore_hidden = self.ignore_hidden;
+        Some(parent)
+    }
+
+    ///Returns the subdirectory `name`, uncached, without scanning this directory. The hidden
+    ///entry setting carries over.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let root = EzDir::new(".", false).unwrap();
+    ///let src = root.child("src").unwrap();
+    ///let lib = src.open_file("lib.rs").unwrap();
+    ///```
+    ///# Errors
+    ///This function will error with [`EzError::NotADirectory`] if `name` isn't a directory, or
+    ///[`EzError::Io`] if it does not exist.
+    pub fn child(&self, name: impl AsRef<Path>) -> Result<EzDir, EzError> {
+        let mut child = Self::new(self.path.join(name), false)?;
+        child.ignore_hidden = self.ignore_hidden;
+        Ok(child)
+    }
+    ///Opens the file `name` in this directory in read-only mode, see [`EzFile::open`].
+    ///# Errors
+    ///This function will error if `name` does not exist, or with
+    ///[`io::ErrorKind::IsADirectory`] if it's a directory.
+    pub fn open_file(&self, name: impl AsRef<Path>) -> Result<EzFile, EzError> {
+        let path = self.path.join(name);
+        if fs::metadata(&path)?.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::IsADirectory, format!("{} is a directory", path.display())).into())
+        }
+        EzFile::open(path)
+    }
+
     ///Returns an iterator yielding references.
     pub fn iter(&self) -> std::slice::Iter<'_, EzEntry> {
         self.into_iter()
     }
-    ///Returns an iterator yielding mutable references.
+    ///Returns an iterator yielding mutable references. Since entries can be changed through
+    ///it, this forgets the stored [`EzDir::total_len`] until the next walk.
     pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, EzEntry> {
         self.into_iter()
     }
+    //like `iter_mut`, but keeps the stored count, for changes that can't affect it
+    fn iter_mut_counted(&mut self) -> std::slice::IterMut<'_, EzEntry> {
+        self.entries.as_deref_mut().unwrap_or_default().iter_mut()
+    }
     
     ///Returns `true` if this directory has been checked.
     ///```
@@ -76,72 +229,1082 @@ impl EzDir {
     #[must_use] pub fn is_cached(&self) -> bool {
         self.entries.is_some()
     }
+    ///Sets whether hidden entries are skipped when this directory is cached or walked. An entry is
+    ///hidden if its name starts with a `.`, or on Windows if it has the hidden attribute. Off by
+    ///default. Subdirectories inherit the setting when they're cached, and entries that are
+    ///already cached stay until the next [`EzDir::cache`] or [`EzDir::walk`].
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new(".", false).unwrap();
+    ///dir.set_ignore_hidden(true);
+    ///dir.cache().unwrap();
+    ///assert!(dir.find(".gitignore").is_none());
+    ///```
+    pub fn set_ignore_hidden(&mut self, ignore: bool) {
+        self.ignore_hidden = ignore;
+    }
+    ///Returns `true` if hidden entries are skipped, see [`EzDir::set_ignore_hidden`].
+    #[must_use] pub fn ignores_hidden(&self) -> bool {
+        self.ignore_hidden
+    }
+
+    //reads this directory's entries from disk, passing the hidden setting down to subdirectories
+    fn scan(&self) -> io::Result<Vec<EzEntry>> {
+        let mut entries = scan(&self.path, self.ignore_hidden)?;
+        for entry in &mut entries {
+            if let EzEntry::Dir(d) = entry {
+                d.ignore_hidden = self.ignore_hidden;
+            }
+        }
+        Ok(entries)
+    }
+
     ///Scans current directory and saves results. This will override/update an already scanned
     ///directory.
     ///```
     ///use ez_fs::EzDir;
     ///
     ///let mut dir = EzDir::new(".", false).unwrap();
-    ///dir.cache();
+    ///dir.cache().unwrap();
     ///assert!(dir.is_cached());
     ///```
-    pub fn cache(&mut self) {
-        self.entries = Some(fs::read_dir(&self.path).unwrap()
-            .filter_map(|e| e.and_then(EzEntry::try_from).ok())
-            .collect())
+    ///# Errors
+    ///This function will error if the directory can't be read, for example if it was removed
+    ///since it was opened. The directory is left as it was.
+    pub fn cache(&mut self) -> Result<(), EzError> {
+        self.entries = Some(self.scan()?);
+        self.total = None;
+        Ok(())
+    }
+
+    ///Re-reads this directory like [`EzDir::cache`], but keeps subdirectories that still exist
+    ///as they were, along with everything walked inside them. New entries are added and deleted
+    ///ones removed. Only this level is re-read, so refresh a subdirectory to pick up changes in
+    ///it.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk(0);
+    ///dir.refresh().unwrap();
+    ///assert!(dir.total_len().is_some());
+    ///```
+    ///# Errors
+    ///This function will error if the directory can't be read. The directory is left as it was.
+    pub fn refresh(&mut self) -> Result<(), EzError> {
+        let fresh = self.scan()?;
+        let mut old: HashMap<PathBuf, EzDir> = self.entries.take().into_iter().flatten()
+            .filter_map(|entry| match entry {
+                EzEntry::Dir(d) => Some((d.path.clone(), d)),
+                EzEntry::File(_) | EzEntry::Symlink(_) => None,
+            })
+            .collect();
+        self.entries = Some(fresh.into_iter()
+            .map(|entry| match entry {
+                EzEntry::Dir(d) => EzEntry::Dir(old.remove(&d.path).unwrap_or(d)),
+                entry => entry,
+            })
+            .collect());
+        if self.total.is_some() {
+            self.count_total();
+        }
+        Ok(())
     }
 
     ///Recursively fills subdirectories up to the specified depth. For example a depth of 1 will
     ///walk at most 1 subdirectory down. A depth of 0 will walk ALL subdirectories. Automatically
     ///caches dir before walking.
+    ///
+    ///This is lossy: a directory that can't be read is silently left uncached, so the tree may
+    ///be incomplete. Use [`EzDir::try_walk`] or [`EzDir::walk_collecting_errors`] to find out.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new(".", true).unwrap();
+    /////recursively walks all subdirectories
+    ///dir.walk(0);
+    ///println!("{dir}");
+    ///```
+    pub fn walk(&mut self, depth: usize) {
+        self.walk_inner(depth, &mut vec![], false, &mut |_, _| {});
+    }
+
+    ///Walks like [`EzDir::walk`], calling `on_dir` with the path and depth of every directory
+    ///right after it's cached, starting with this one at depth 0. Handy for progress reporting,
+    ///the resulting tree is the same.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///let mut count = 0;
+    ///dir.walk_with(0, |path, depth| {
+    ///    count += 1;
+    ///    eprintln!("{count} {depth} {}", path.display());
+    ///});
+    ///```
+    pub fn walk_with<F: FnMut(&Path, usize)>(&mut self, depth: usize, mut on_dir: F) {
+        self.walk_inner(depth, &mut vec![], false, &mut on_dir);
+    }
+
+    ///Walks like [`EzDir::walk`], but stops at the first directory that can't be read.
+    ///Everything walked up to that point stays cached.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.try_walk(0).unwrap();
+    ///assert!(dir.is_cached());
+    ///```
+    ///# Errors
+    ///This function will error if a directory can't be read.
+    pub fn try_walk(&mut self, depth: usize) -> Result<(), EzError> {
+        let mut errors = vec![];
+        self.walk_inner(depth, &mut errors, true, &mut |_, _| {});
+        errors.pop().map_or(Ok(()), Err)
+    }
+
+    ///Walks like [`EzDir::walk`], but returns an error for every directory that couldn't be
+    ///read, which are left uncached. The rest of the tree is walked as usual.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///for e in dir.walk_collecting_errors(0) {
+    ///    eprintln!("{e}");
+    ///}
+    ///```
+    pub fn walk_collecting_errors(&mut self, depth: usize) -> Vec<EzError> {
+        let mut errors = vec![];
+        self.walk_inner(depth, &mut errors, false, &mut |_, _| {});
+        errors
+    }
+
+    //shared walk, recording directories that failed to cache and optionally stopping at the first
+    fn walk_inner(&mut self, depth: usize, errors: &mut Vec<EzError>, fail_fast: bool, on_dir: &mut dyn FnMut(&Path, usize)) {
+        fn fill(dir: &mut EzDir, curr: usize, max: usize, errors: &mut Vec<EzError>, fail_fast: bool, on_dir: &mut dyn FnMut(&Path, usize)) {
+            for entry in dir.iter_mut() {
+                if let EzEntry::Dir(d) = entry {
+                    match d.cache() {
+                        Ok(()) => {
+                            on_dir(&d.path, curr + 1);
+                            if curr < max-1 {fill(d, curr + 1, max, errors, fail_fast, on_dir)}
+                        },
+                        Err(e) => errors.push(e),
+                    }
+                    if fail_fast && !errors.is_empty() {return}
+                }
+            }
+        }
+
+        match self.cache() {
+            Ok(()) => {
+                on_dir(&self.path, 0);
+                fill(self, 0, if depth > 0 { depth } else { usize::MAX }, errors, fail_fast, on_dir);
+            },
+            Err(e) => errors.push(e),
+        }
+        self.count_total();
+    }
+
+    ///Streams every file in the tree depth-first, reading each directory from disk only when
+    ///the iterator reaches it, so memory stays bounded no matter how big the tree is. Unlike
+    ///[`EzDir::flatten_all`] nothing is cached, and anything already cached is ignored.
+    ///
+    ///An error reading a directory or opening a file is yielded in its place and the walk
+    ///carries on without it. Symlinks are not followed, and they and other special files are
+    ///skipped. Hidden entries are skipped if [`EzDir::set_ignore_hidden`] is on.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let dir = EzDir::new("src", false).unwrap();
+    ///for file in dir.walk_iter().filter_map(Result::ok) {
+    ///    println!("{file}");
+    ///}
+    ///```
+    pub fn walk_iter(self) -> impl Iterator<Item = Result<EzFile, EzError>> {
+        WalkIter { root: Some(self.path), stack: vec![], skip_hidden: self.ignore_hidden }
+    }
+
+    ///Walks like [`EzDir::walk`], but in steps: every call to `next` caches at most `batch`
+    ///directories and then hands control back. The walk is done once the iterator ends. An error
+    ///reading a directory is yielded once and the walk carries on without it.
+    ///
+    ///This keeps a blocking walk from hogging an async executor without porting it to async.
+    ///Either drive the whole thing inside `spawn_blocking`, or yield between batches:
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///for step in dir.walk_chunked(0, 64) {
+    ///    step.unwrap();
+    ///    //in an async task: tokio::task::yield_now().await;
+    ///}
+    ///assert!(dir.is_cached());
+    ///```
+    pub fn walk_chunked(&mut self, depth: usize, batch: usize) -> impl Iterator<Item = Result<(), EzError>> + '_ {
+        WalkChunks {
+            root: self,
+            pending: vec![(vec![], 0)],
+            batch: batch.max(1),
+            max: if depth > 0 { depth } else { usize::MAX },
+        }
+    }
+
+    ///Walks like [`EzDir::walk`], but reads each level of the tree on up to `threads` worker
+    ///threads at once. Keep `threads` low on latency-bound storage like network filesystems,
+    ///where too many concurrent reads can end up slower than walking serially. The resulting tree
+    ///is the same as [`EzDir::walk`].
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk_parallel_with_threads(0, 4).unwrap();
+    ///assert!(dir.is_cached());
+    ///```
+    ///# Errors
+    ///This function will error if a directory can't be read, after finishing the level it was
+    ///found on. Directories that failed are left uncached.
+    pub fn walk_parallel_with_threads(&mut self, depth: usize, threads: usize) -> Result<(), EzError> {
+        let max = if depth > 0 { depth } else { usize::MAX };
+        let threads = threads.max(1);
+
+        let mut level: Vec<&mut EzDir> = vec![self];
+        let mut curr = 0;
+        let mut result = Ok(());
+        while !level.is_empty() && result.is_ok() {
+            let chunk = level.len().div_ceil(threads);
+            result = std::thread::scope(|s| {
+                let workers: Vec<_> = level.chunks_mut(chunk)
+                    .map(|dirs| s.spawn(move || {
+                        let mut result = Ok(());
+                        for dir in dirs {
+                            match dir.scan() {
+                                Ok(entries) => dir.entries = Some(entries),
+                                Err(e) => {
+                                    dir.entries = None;
+                                    result = result.and(Err(e.into()));
+                                },
+                            }
+                        }
+                        result
+                    }))
+                    .collect();
+                workers.into_iter()
+                    .map(|w| w.join().expect("walk worker panicked"))
+                    .fold(Ok(()), Result::and)
+            });
+
+            level = if curr < max {
+                level.into_iter()
+                    .flat_map(|dir| dir.iter_mut().filter_map(|e| match e {
+                        EzEntry::Dir(d) => Some(d),
+                        EzEntry::File(_) | EzEntry::Symlink(_) => None,
+                    }))
+                    .collect()
+            } else {
+                vec![]
+            };
+            curr += 1;
+        }
+
+        self.count_total();
+        result
+    }
+
+    ///Walks like [`EzDir::walk`], but caches subdirectories concurrently on the rayon thread
+    ///pool. The resulting tree is the same as [`EzDir::walk`], including leaving directories
+    ///that can't be read uncached. Requires the `rayon` feature.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk_parallel(0);
+    ///assert!(dir.is_cached());
+    ///```
+    #[cfg(feature = "rayon")]
+    pub fn walk_parallel(&mut self, depth: usize) {
+        use rayon::prelude::*;
+
+        fn fill(dir: &mut EzDir, curr: usize, max: usize) {
+            if let Some(entries) = &mut dir.entries {
+                entries.par_iter_mut().for_each(|entry| {
+                    if let EzEntry::Dir(d) = entry {
+                        if d.cache().is_ok() && curr < max-1 {fill(d, curr + 1, max)}
+                    }
+                });
+            }
+        }
+
+        let _ = self.cache();
+        fill(self, 0, if depth > 0 { depth } else { usize::MAX });
+        self.count_total();
+    }
+
+    //recomputes and stores the recursive entry count of every cached directory
+    fn count_total(&mut self) -> usize {
+        if !self.is_cached() {
+            self.total = None;
+            return 0
+        }
+        let total = self.iter_mut()
+            .map(|entry| match entry {
+                EzEntry::File(_) | EzEntry::Symlink(_) => 1,
+                EzEntry::Dir(d) => 1 + d.count_total(),
+            })
+            .sum();
+        self.total = Some(total);
+        total
+    }
+
+    ///Walks like [`EzDir::walk`], but only keeps files whose path matches any of the `include`
+    ///patterns and none of the `exclude` patterns. Directories matching an `exclude` pattern are
+    ///dropped without being descended into. An empty `include` keeps every file.
+    ///
+    ///Patterns are matched against paths relative to this directory, `*` and `?` stay within a
+    ///single path component while `**` matches any number of them.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk_globs(0, &["**/*.rs"], &["glob.rs"]).unwrap();
+    ///for file in dir.flatten() {
+    ///    assert_eq!(file.extension(), Some("rs"));
+    ///    assert!(!file.path().ends_with("glob.rs"));
+    ///}
+    ///```
+    ///# Errors
+    ///This function will error if a directory can't be read.
+    pub fn walk_globs(&mut self, depth: usize, include: &[&str], exclude: &[&str]) -> Result<(), EzError> {
+        fn fill(dir: &mut EzDir, root: &Path, curr: usize, max: usize, include: &[&str], exclude: &[&str]) -> io::Result<()> {
+            let mut entries = dir.scan()?;
+            entries.retain(|entry| {
+                let rel = relative(root, entry.path());
+                let excluded = exclude.iter().any(|p| glob::matches(p, &rel));
+                match entry {
+                    EzEntry::File(_) | EzEntry::Symlink(_) => !excluded && (include.is_empty() || include.iter().any(|p| glob::matches(p, &rel))),
+                    EzEntry::Dir(_) => !excluded,
+                }
+            });
+            dir.entries = Some(entries);
+
+            if curr < max {
+                for entry in dir.iter_mut() {
+                    if let EzEntry::Dir(d) = entry {
+                        fill(d, root, curr + 1, max, include, exclude)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        let root = self.path.clone();
+        let max = if depth > 0 { depth } else { usize::MAX };
+        fill(self, &root, 0, max, include, exclude)?;
+        self.count_total();
+        Ok(())
+    }
+
+    ///Swaps this directory with `other` on disk, so each path now holds the other's contents.
+    ///On Linux this is a single atomic `renameat2(RENAME_EXCHANGE)`, elsewhere (or on filesystems
+    ///that don't support it) it falls back to three renames through a temporary name, which is
+    ///not atomic. Both paths stay the same, and the cached entries are swapped to match.
+    ///```
+    ///use ez_fs::EzDir;
+    ///# std::fs::create_dir_all("foo_current").unwrap();
+    ///# std::fs::create_dir_all("foo_new").unwrap();
+    ///# std::fs::write("foo_new/index.html", b"v2").unwrap();
+    ///
+    ///let mut current = EzDir::new("foo_current", true).unwrap();
+    ///let mut new = EzDir::new("foo_new", true).unwrap();
+    ///current.swap_with(&mut new).unwrap();
+    ///
+    ///assert_eq!(current.len(), Some(1));
+    ///assert_eq!(new.len(), Some(0));
+    ///# std::fs::remove_dir_all("foo_current").unwrap();
+    ///# std::fs::remove_dir_all("foo_new").unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if either directory can't be renamed.
+    pub fn swap_with(&mut self, other: &mut EzDir) -> Result<(), EzError> {
+        exchange(&self.path, &other.path)?;
+
+        let (a, b) = (self.path.clone(), other.path.clone());
+        std::mem::swap(&mut self.entries, &mut other.entries);
+        std::mem::swap(&mut self.total, &mut other.total);
+        self.iter_mut_counted().for_each(|e| e.rebase(&b, &a));
+        other.iter_mut_counted().for_each(|e| e.rebase(&a, &b));
+        Ok(())
+    }
+
+    ///Renames or moves the directory to `new_path` and updates the paths of everything cached
+    ///under it to match. Moves across filesystems aren't supported and return the error from
+    ///the OS.
+    ///# Errors
+    ///This function will error if the directory can't be moved to `new_path`.
+    pub fn rename(&mut self, new_path: impl AsRef<Path>) -> Result<(), EzError> {
+        let new_path = new_path.as_ref();
+        fs::rename(&self.path, new_path)?;
+        let old = std::mem::replace(&mut self.path, new_path.to_owned());
+        self.iter_mut_counted().for_each(|e| e.rebase(&old, new_path));
+        Ok(())
+    }
+
+    //rewrites every cached path after the directory has been moved
+    fn rebase(&mut self, from: &Path, to: &Path) {
+        if let Ok(rest) = self.path.strip_prefix(from) {
+            self.path = to.join(rest);
+        }
+        self.iter_mut_counted().for_each(|e| e.rebase(from, to));
+    }
+
+    ///Walks the whole directory and returns the most recent modification time of any file in it,
+    ///or [`None`] if there are no files.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///assert!(dir.newest_modified().unwrap().is_some());
+    ///```
+    ///# Errors
+    ///This function will error if the platform doesn't support modification times.
+    pub fn newest_modified(&mut self) -> Result<Option<SystemTime>, EzError> {
+        self.try_walk(0)?;
+        let mut newest = None;
+        for file in self.iter_files() {
+            newest = newest.max(Some(file.modified()?));
+        }
+        Ok(newest)
+    }
+
+    ///Walks the whole directory and returns the oldest modification time of any file in it,
+    ///or [`None`] if there are no files.
+    ///# Errors
+    ///This function will error if the platform doesn't support modification times.
+    pub fn oldest_modified(&mut self) -> Result<Option<SystemTime>, EzError> {
+        self.try_walk(0)?;
+        let mut oldest: Option<SystemTime> = None;
+        for file in self.iter_files() {
+            let time = file.modified()?;
+            oldest = Some(oldest.map_or(time, |t| t.min(time)));
+        }
+        Ok(oldest)
+    }
+
+    ///Walks the whole directory and returns the paths of every file modified after `since`.
+    ///Modification times are taken from the metadata read during the walk, and files whose
+    ///time can't be read are skipped.
+    ///```
+    ///use ez_fs::EzDir;
+    ///use std::time::SystemTime;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///assert!(dir.changed_since(SystemTime::now()).unwrap().is_empty());
+    ///```
+    ///# Errors
+    ///This function will error if a directory can't be read.
+    pub fn changed_since(&mut self, since: SystemTime) -> Result<Vec<PathBuf>, EzError> {
+        self.try_walk(0)?;
+        Ok(self.iter_files()
+            .filter(|file| file.modified().is_ok_and(|time| time > since))
+            .map(|file| file.path().to_owned())
+            .collect())
+    }
+
+    ///Walks the whole directory and sets the modification time of every file to `time`
+    ///(usually [`SystemTime::now`]), returning how many files were touched. Handy for forcing
+    ///build tools to rebuild.
+    ///```
+    ///use ez_fs::EzDir;
+    ///use std::time::SystemTime;
+    ///# std::fs::create_dir_all("foo_touch_dir").unwrap();
+    ///# std::fs::write("foo_touch_dir/a.txt", b"").unwrap();
+    ///
+    ///let mut dir = EzDir::new("foo_touch_dir", false).unwrap();
+    ///assert_eq!(dir.touch_all(SystemTime::now()).unwrap(), 1);
+    ///```
+    ///# Errors
+    ///This function will error if a file's time can't be set.
+    pub fn touch_all(&mut self, time: SystemTime) -> Result<usize, EzError> {
+        self.try_walk(0)?;
+        let mut count = 0;
+        for file in self.iter_files_mut() {
+            file.set_modified(time)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    ///Walks the whole directory and returns how many bytes each immediate subdirectory holds,
+    ///counting everything under it, like `du`. Files directly in this directory are summed into
+    ///an entry for this directory's own path. Sorted from largest to smallest.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///for (path, size) in dir.size_breakdown().unwrap() {
+    ///    println!("{size:>12} {}", path.display());
+    ///}
+    ///```
+    ///# Errors
+    ///This function will error if a directory can't be read.
+    pub fn size_breakdown(&mut self) -> Result<Vec<(PathBuf, u64)>, EzError> {
+        self.try_walk(0)?;
+        let mut loose = 0;
+        let mut sizes = vec![];
+        for entry in self.iter() {
+            match entry {
+                EzEntry::File(file) => loose += file.len(),
+                EzEntry::Dir(dir) => sizes.push((dir.path().to_owned(), dir.total_size())),
+                EzEntry::Symlink(_) => {},
+            }
+        }
+        sizes.push((self.path.clone(), loose));
+        sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        Ok(sizes)
+    }
+
+    ///Walks the whole directory and returns every symlink whose target doesn't exist, as
+    ///`(link, target)` pairs with the target exactly as stored in the link.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///assert!(dir.broken_symlinks().unwrap().is_empty());
+    ///```
+    ///# Errors
+    ///This function will error if a directory or link can't be read.
+    pub fn broken_symlinks(&mut self) -> Result<Vec<(PathBuf, PathBuf)>, EzError> {
+        fn search(dir: &EzDir, broken: &mut Vec<(PathBuf, PathBuf)>) -> io::Result<()> {
+            for entry in dir {
+                match entry {
+                    EzEntry::Symlink(link) if fs::metadata(link).is_err() => broken.push((link.clone(), fs::read_link(link)?)),
+                    EzEntry::Dir(d) => search(d, broken)?,
+                    _ => {},
+                }
+            }
+            Ok(())
+        }
+
+        self.try_walk(0)?;
+        let mut broken = vec![];
+        search(self, &mut broken)?;
+        Ok(broken)
+    }
+
+    ///Walks the whole directory and returns the number of files and their total size in bytes
+    ///for each file extension. Extensions are lowercased, and files without one are counted under
+    ///an empty string.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///let stats = dir.stats_by_extension();
+    ///let (count, bytes) = stats["rs"];
+    ///println!("rs: {count} files, {bytes} bytes");
+    ///```
+    pub fn stats_by_extension(&mut self) -> HashMap<String, (usize, u64)> {
+        self.walk(0);
+        let mut stats: HashMap<String, (usize, u64)> = HashMap::new();
+        for file in self.iter_files() {
+            let ext = file.path().extension()
+                .map_or(String::new(), |ext| ext.to_string_lossy().to_lowercase());
+            let entry = stats.entry(ext).or_default();
+            entry.0 += 1;
+            entry.1 += file.len();
+        }
+        stats
+    }
+
+    ///Walks the whole directory and returns the entry path with the most components, along with
+    ///its component count, or [`None`] if the directory is empty. Ties go to the first entry
+    ///found. Handy for checking paths against platform length limits before copying or archiving.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///let (depth, path) = dir.deepest_path().unwrap().unwrap();
+    ///assert_eq!(depth, path.components().count());
+    ///```
+    ///# Errors
+    ///This function will error if a directory can't be read.
+    pub fn deepest_path(&mut self) -> Result<Option<(usize, PathBuf)>, EzError> {
+        fn search<'a>(dir: &'a EzDir, deepest: &mut Option<(usize, &'a Path)>) {
+            for entry in dir {
+                let path = entry.path();
+                let depth = path.components().count();
+                if deepest.is_none_or(|(max, _)| depth > max) {
+                    *deepest = Some((depth, path));
+                }
+                if let EzEntry::Dir(d) = entry {
+                    search(d, deepest);
+                }
+            }
+        }
+
+        self.try_walk(0)?;
+        let mut deepest = None;
+        search(self, &mut deepest);
+        Ok(deepest.map(|(depth, path)| (depth, path.to_owned())))
+    }
+
+    ///Walks the whole directory and groups files with identical contents. Only files that share
+    ///their size with another file are hashed with [`EzFile::hash`], and files whose hashes match
+    ///are then compared byte by byte, so a hash collision never makes a false duplicate. Files
+    ///without a duplicate are left out, so every group has at least two paths. Paths are sorted
+    ///within each group, and groups by their first path.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///for paths in dir.find_duplicates().unwrap() {
+    ///    println!("{paths:?}");
+    ///}
+    ///```
+    ///# Errors
+    ///This function will error if a directory or file can't be read.
+    pub fn find_duplicates(&mut self) -> Result<Vec<Vec<PathBuf>>, EzError> {
+        self.try_walk(0)?;
+        let mut by_len: HashMap<u64, Vec<&mut EzFile>> = HashMap::new();
+        for file in self.iter_files_mut() {
+            by_len.entry(file.len()).or_default().push(file);
+        }
+
+        let mut groups = vec![];
+        for files in by_len.into_values().filter(|files| files.len() > 1) {
+            //hashes only narrow things down within one size, the bytes decide
+            let mut by_hash: HashMap<u64, Vec<Vec<PathBuf>>> = HashMap::new();
+            for file in files {
+                let candidates = by_hash.entry(file.hash()?).or_default();
+                let path = file.path().to_owned();
+                let mut same = None;
+                for (i, group) in candidates.iter().enumerate() {
+                    if same_contents(&group[0], &path)? {
+                        same = Some(i);
+                        break
+                    }
+                }
+                match same {
+                    Some(i) => candidates[i].push(path),
+                    None => candidates.push(vec![path]),
+                }
+            }
+            groups.extend(by_hash.into_values().flatten().filter(|paths| paths.len() > 1));
+        }
+        for paths in &mut groups {
+            paths.sort();
+        }
+        groups.sort();
+        Ok(groups)
+    }
+
+    ///Returns an iterator over references to every cached file in the tree, depth-first.
+    ///Unlike [`EzDir::flatten`] this doesn't consume the directory, so it can be traversed as
+    ///many times as needed. Only covers what has been walked.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new(".", false).unwrap();
+    ///dir.walk(1);
+    ///let count = dir.iter_files().count();
+    ///assert_eq!(dir.iter_files().count(), count);
+    ///```
+    pub fn iter_files(&self) -> impl Iterator<Item = &EzFile> {
+        Files { stack: vec![self.iter()] }
+    }
+    ///Returns an iterator over mutable references to every cached file in the tree, depth-first.
+    pub fn iter_files_mut(&mut self) -> impl Iterator<Item = &mut EzFile> {
+        FilesMut { stack: vec![self.iter_mut_counted()] }
+    }
+
+    ///Counts the cached entries anywhere in the tree, files, directories and symlinks alike,
+    ///for which `pred` returns `true`. Unlike [`EzDir::len`] this isn't limited to one level.
+    ///Only covers what has been walked.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk(0);
+    ///let rs = dir.count_where(|e| e.path().extension().is_some_and(|ext| ext == "rs"));
+    ///assert!(rs > 0);
+    ///```
+    pub fn count_where<F: Fn(&EzEntry) -> bool>(&self, pred: F) -> usize {
+        self.entries_recursive().filter(|entry| pred(entry)).count()
+    }
+    ///Returns `true` if `pred` returns `true` for any cached entry in the tree, see
+    ///[`EzDir::count_where`]. Stops at the first match.
+    pub fn any<F: Fn(&EzEntry) -> bool>(&self, pred: F) -> bool {
+        self.entries_recursive().any(pred)
+    }
+    ///Returns `true` if `pred` returns `true` for every cached entry in the tree, see
+    ///[`EzDir::count_where`]. Stops at the first mismatch, and is `true` for an empty or
+    ///uncached directory.
+    pub fn all<F: Fn(&EzEntry) -> bool>(&self, pred: F) -> bool {
+        self.entries_recursive().all(pred)
+    }
+    //every cached entry in the tree, depth-first, each directory before its contents
+    fn entries_recursive(&self) -> Entries<'_> {
+        Entries { stack: vec![self.iter()] }
+    }
+
+    ///Returns every cached file in the tree with the extension `ext`, which may be given with or
+    ///without its leading dot. Matching is case-sensitive. Like [`EzDir::flatten`], this only
+    ///covers what has been walked.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk(0);
+    ///for file in dir.filter_extension("rs") {
+    ///    println!("{file}");
+    ///}
+    ///```
+    #[must_use] pub fn filter_extension(&self, ext: &str) -> Vec<&EzFile> {
+        let ext = ext.strip_prefix('.').unwrap_or(ext);
+        self.iter_files().filter(|file| file.extension() == Some(ext)).collect()
+    }
+    ///Consuming version of [`EzDir::filter_extension`].
+    #[must_use] pub fn into_filter_extension(self, ext: &str) -> Vec<EzFile> {
+        let ext = ext.strip_prefix('.').unwrap_or(ext);
+        self.flatten().into_iter().filter(|file| file.extension() == Some(ext)).collect()
+    }
+
+    ///Returns every cached file whose path relative to this directory matches the glob
+    ///`pattern`. `*` and `?` stay within a single path component while `**` matches any number
+    ///of them, so `*.rs` only looks at this level and `**/*.rs` at every level. Matching is
+    ///case-sensitive, and like [`EzDir::flatten`] only covers what has been walked.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk(0);
+    ///assert!(!dir.glob("**/*.rs").is_empty());
+    ///assert!(dir.glob("*.toml").is_empty());
+    ///```
+    #[must_use] pub fn glob(&self, pattern: &str) -> Vec<&EzFile> {
+        self.iter_files()
+            .filter(|file| glob::matches(pattern, &relative(&self.path, file.path())))
+            .collect()
+    }
+
+    ///Searches the cached tree depth-first and returns the first file or directory whose file
+    ///name (not full path) is `name`. Like [`EzDir::flatten`], this only searches what has been
+    ///walked.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk(0);
+    ///assert!(dir.find("lib.rs").is_some_and(|e| e.is_file()));
+    ///```
+    #[must_use] pub fn find(&self, name: &str) -> Option<&EzEntry> {
+        for entry in self {
+            if entry.path().file_name() == Some(name.as_ref()) {
+                return Some(entry)
+            }
+            if let EzEntry::Dir(dir) = entry {
+                if let Some(found) = dir.find(name) {
+                    return Some(found)
+                }
+            }
+        }
+        None
+    }
+    ///Like [`EzDir::find`], but returns every match, in depth-first order.
+    #[must_use] pub fn find_all(&self, name: &str) -> Vec<&EzEntry> {
+        fn search<'a>(dir: &'a EzDir, name: &str, found: &mut Vec<&'a EzEntry>) {
+            for entry in dir {
+                if entry.path().file_name() == Some(name.as_ref()) {
+                    found.push(entry);
+                }
+                if let EzEntry::Dir(d) = entry {
+                    search(d, name, found);
+                }
+            }
+        }
+
+        let mut found = vec![];
+        search(self, name, &mut found);
+        found
+    }
+
+    ///Sorts the cached entries by name in natural order, comparing runs of digits by their
+    ///numeric value so `file2` comes before `file10`. The sort is stable, only affects this
+    ///level, and does nothing if the directory hasn't been cached.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new(".", true).unwrap();
+    ///dir.sort_natural();
+    ///```
+    pub fn sort_natural(&mut self) {
+        self.sort_by(|a, b| natural_cmp(&entry_name(a), &entry_name(b)));
+    }
+    ///Sorts the cached entries by name, comparing plain strings so `file10` comes before
+    ///`file2`. Same rules as [`EzDir::sort_natural`] otherwise.
+    pub fn sort_by_name(&mut self) {
+        self.sort_by(|a, b| entry_name(a).cmp(&entry_name(b)));
+    }
+    ///Sorts the cached entries by size, smallest first, with directories after every file.
+    ///Sizes come from the metadata read when the directory was cached. Same rules as
+    ///[`EzDir::sort_natural`] otherwise.
+    pub fn sort_by_size(&mut self) {
+        self.sort_by_key(|entry| match entry {
+            EzEntry::File(file) => (false, file.len()),
+            EzEntry::Symlink(_) => (false, 0),
+            EzEntry::Dir(_) => (true, 0),
+        });
+    }
+    ///Sorts the cached entries with a custom comparator. Same rules as [`EzDir::sort_natural`].
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    /////directories first
+    ///let mut dir = EzDir::new(".", true).unwrap();
+    ///dir.sort_by(|a, b| b.is_dir().cmp(&a.is_dir()));
+    ///```
+    pub fn sort_by<F: FnMut(&EzEntry, &EzEntry) -> std::cmp::Ordering>(&mut self, cmp: F) {
+        if let Some(entries) = &mut self.entries {
+            entries.sort_by(cmp);
+        }
+    }
+    //same rules as `sort_by`
+    fn sort_by_key<K: Ord, F: FnMut(&EzEntry) -> K>(&mut self, key: F) {
+        if let Some(entries) = &mut self.entries {
+            entries.sort_by_key(key);
+        }
+    }
+
+    ///Keeps only the cached entries for which `predicate` returns `true`, like
+    ///[`Vec::retain`]. Only affects this level, and does nothing if the directory hasn't been
+    ///cached.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new(".", true).unwrap();
+    ///dir.retain(|e| e.name().is_some_and(|n| !n.starts_with('.')));
+    ///assert!(dir.find(".gitignore").is_none());
+    ///```
+    pub fn retain<F: FnMut(&EzEntry) -> bool>(&mut self, predicate: F) {
+        if let Some(entries) = &mut self.entries {
+            entries.retain(predicate);
+        }
+        if self.total.is_some() {
+            self.count_total();
+        }
+    }
+
+    ///Returns entry reference if the given index exists. Returns [`None`] if the index is out of bounds or the
+    ///directory hasn't been cached.
+    #[must_use] pub fn get(&self, idx:usize) -> Option<&EzEntry> {
+        self.entries.as_ref()?.get(idx)
+    }
+    ///Returns mutable entry reference if the given index exists. Returns [`None`] if the index is out of bounds or the
+    ///directory hasn't been cached. Like [`EzDir::iter_mut`], this forgets the stored [`EzDir::total_len`].
+    pub fn get_mut(&mut self, idx:usize) -> Option<&mut EzEntry> {
+        self.total = None;
+        self.entries.as_mut()?.get_mut(idx)
+    }
+    
+    ///Returns the cached entries as a slice, or [`None`] if the directory hasn't been cached.
+    ///Handy for slice methods like `binary_search` or `chunks` after sorting.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new(".", true).unwrap();
+    ///dir.sort_by_name();
+    ///let entries = dir.entries().unwrap();
+    ///assert!(entries.binary_search_by(|e| e.name().cmp(&Some("src"))).is_ok());
+    ///```
+    #[must_use] pub fn entries(&self) -> Option<&[EzEntry]> {
+        self.entries.as_deref()
+    }
+    ///Returns the cached entries as a mutable slice, or [`None`] if the directory hasn't been
+    ///cached. Like [`EzDir::iter_mut`], this forgets the stored [`EzDir::total_len`].
+    pub fn entries_mut(&mut self) -> Option<&mut [EzEntry]> {
+        self.total = None;
+        self.entries.as_deref_mut()
+    }
+
+    ///Returns the length of the directory or [`None`] if it hasn't been cached.
+    #[must_use] pub fn len(&self) -> Option<usize> {
+        self.entries.as_ref().map(std::vec::Vec::len)
+    }
+    ///Returns the number of entries in the whole tree, counting files, directories and symlinks,
+    ///or [`None`] if it hasn't been walked. The count is stored by [`EzDir::walk`], so this is
+    ///free to call repeatedly, and only covers the walked depth. Mutable access to the entries,
+    ///such as [`EzDir::iter_mut`] or [`EzDir::get_mut`], forgets it until the next walk.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new(".", true).unwrap();
+    ///assert_eq!(dir.total_len(), None);
+    ///
+    ///dir.walk(1);
+    ///assert!(dir.total_len() >= dir.len());
+    ///```
+    #[must_use] pub fn total_len(&self) -> Option<usize> {
+        self.total
+    }
+    ///Returns the combined size in bytes of every cached file in the tree. Directories themselves
+    ///don't count, and sizes come from the metadata read when each directory was cached. Like
+    ///[`EzDir::flatten`], this only covers what has been walked.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk(0);
+    ///println!("{} files, {} bytes", dir.file_count(), dir.total_size());
+    ///```
+    #[must_use] pub fn total_size(&self) -> u64 {
+        self.iter_files().map(EzFile::len).sum()
+    }
+    ///Returns the number of cached files in the tree, not counting directories.
+    #[must_use] pub fn file_count(&self) -> usize {
+        self.iter_files().count()
+    }
+    ///Returns a boolean of whether or not the dir is empty, or [`None`] if it hasn't been cached.
+    pub fn is_empty(&self) -> Option<bool> {
+        self.entries.as_ref().map(std::vec::Vec::is_empty)
+    } 
+
+    
+    ///Compares the cached entries of this tree against `other` by their paths relative to each
+    ///root, sorted and joined with `/`. Only covers what has been walked, and contents aren't
+    ///compared, so pair it with [`EzFile::hash`] to check the paths in both.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut a = EzDir::new("src", false).unwrap();
+    ///let mut b = EzDir::new("src", false).unwrap();
+    ///a.walk(0);
+    ///b.walk(0);
+    ///let diff = a.diff(&b);
+    ///assert!(diff.only_in_self.is_empty() && diff.only_in_other.is_empty());
+    ///```
+    #[must_use] pub fn diff(&self, other: &EzDir) -> DirDiff {
+        let paths = |dir: &EzDir| -> BTreeSet<String> {
+            dir.entries_recursive().map(|entry| relative(&dir.path, entry.path())).collect()
+        };
+        let ours = paths(self);
+        let theirs = paths(other);
+        DirDiff {
+            only_in_self: ours.difference(&theirs).cloned().collect(),
+            only_in_other: theirs.difference(&ours).cloned().collect(),
+            in_both: ours.intersection(&theirs).cloned().collect(),
+        }
+    }
+
+    ///Renders the cached tree like `tree(1)`, starting with this directory's path. Directories
+    ///get a trailing `/` and symlinks show their target. Uncached subdirectories are listed
+    ///without contents, so walk first to show the whole tree.
     ///```
     ///use ez_fs::EzDir;
     ///
-    ///let mut dir = EzDir::new(".", true).unwrap();
-    /////recursively walks all subdirectories
+    ///let mut dir = EzDir::new("src", false).unwrap();
     ///dir.walk(0);
-    ///println!("{dir}");
+    ///println!("{}", dir.tree_string());
     ///```
-    pub fn walk(&mut self, depth: usize) {
-        fn fill(dir: &mut EzDir, curr: usize, max: usize) {
-            for entry in dir.iter_mut() {
+    #[must_use] pub fn tree_string(&self) -> String {
+        fn draw(dir: &EzDir, prefix: &str, out: &mut String) {
+            use std::fmt::Write;
+
+            let len = dir.len().unwrap_or(0);
+            for (i, entry) in dir.iter().enumerate() {
+                let last = i + 1 == len;
+                let name = entry_name(entry);
+                let connector = if last { "└── " } else { "├── " };
+                //writing to a String can't fail
+                let _ = match entry {
+                    EzEntry::File(_) => writeln!(out, "{prefix}{connector}{name}"),
+                    EzEntry::Dir(_) => writeln!(out, "{prefix}{connector}{name}/"),
+                    EzEntry::Symlink(link) => match fs::read_link(link) {
+                        Ok(target) => writeln!(out, "{prefix}{connector}{name} -> {}", target.display()),
+                        Err(_) => writeln!(out, "{prefix}{connector}{name}"),
+                    },
+                };
                 if let EzEntry::Dir(d) = entry {
-                    d.cache();
-                    if curr < max-1 {fill(d, curr + 1, max)}
+                    draw(d, &format!("{prefix}{}", if last { "    " } else { "│   " }), out);
                 }
-            } 
+            }
         }
-        
-        self.cache(); 
 
-        if depth > 0 {
-            fill(self, 0, depth);
-        } else {
-            fill(self, 0, usize::MAX);
-        }
+        let mut out = format!("{}\n", self.path.display());
+        draw(self, "", &mut out);
+        out
     }
 
-    ///Returns entry reference if the given index exists. Returns [`None`] if the index is out of bounds or the
-    ///directory hasn't been cached.
-    #[must_use] pub fn get(&self, idx:usize) -> Option<&EzEntry> {
-        self.entries.as_ref()?.get(idx)
+    ///Copies the directory to `dest`, creating it and any missing parents, and returns the copy
+    ///with the same structure cached. Only cached entries are copied: walk first to copy the
+    ///whole tree. Subdirectories that haven't been cached are created empty.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk(0);
+    ///let copy = dir.copy_to("foo_src_copy").unwrap();
+    ///assert_eq!(copy.total_len(), dir.total_len());
+    ///# copy.remove_all().unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if `dest` or anything in it can't be created.
+    pub fn copy_to(&self, dest: impl AsRef<Path>) -> Result<EzDir, EzError> {
+        fn copy(dir: &EzDir, dest: &Path) -> io::Result<EzDir> {
+            fs::create_dir_all(dest)?;
+            let entries = match &dir.entries {
+                Some(entries) => Some(entries.iter()
+                    .map(|entry| {
+                        let to = dest.join(entry.path().file_name().unwrap_or_default());
+                        Ok(match entry {
+                            EzEntry::File(file) => EzEntry::File(file.copy_to(to)?),
+                            EzEntry::Dir(d) => EzEntry::Dir(copy(d, &to)?),
+                            EzEntry::Symlink(link) => {
+                                copy_symlink(link, &to)?;
+                                EzEntry::Symlink(to)
+                            },
+                        })
+                    })
+                    .collect::<io::Result<_>>()?),
+                None => None,
+            };
+            let mut copy = EzDir::from_parts(dest.to_owned(), entries);
+            copy.ignore_hidden = dir.ignore_hidden;
+            Ok(copy)
+        }
+
+        let mut copy = copy(self, dest.as_ref())?;
+        copy.count_total();
+        Ok(copy)
     }
-    ///Returns mutable entry reference if the given index exists. Returns [`None`] if the index is out of bounds or the
-    ///directory hasn't been cached.
-    pub fn get_mut(&mut self, idx:usize) -> Option<&mut EzEntry> {
-        self.entries.as_mut()?.get_mut(idx)
+
+    ///Deletes this directory, which has to be empty. Any cached files are closed first.
+    ///# Errors
+    ///This function will error if the directory isn't empty or can't be removed.
+    pub fn remove(self) -> Result<(), EzError> {
+        let path = self.path.clone();
+        drop(self);
+        Ok(fs::remove_dir(path)?)
     }
-    
-    ///Returns the length of the directory or [`None`] if it hasn't been cached.
-    #[must_use] pub fn len(&self) -> Option<usize> {
-        self.entries.as_ref().map(std::vec::Vec::len)
+    ///Deletes this directory along with everything in it, whether cached or not. Any cached
+    ///files are closed first.
+    ///```
+    ///use ez_fs::EzDir;
+    ///# std::fs::create_dir_all("foo_remove_all/sub").unwrap();
+    ///# std::fs::write("foo_remove_all/sub/a.txt", b"").unwrap();
+    ///
+    ///let mut dir = EzDir::new("foo_remove_all", false).unwrap();
+    ///dir.walk(0);
+    ///dir.remove_all().unwrap();
+    ///assert!(!std::path::Path::new("foo_remove_all").exists());
+    ///```
+    ///# Errors
+    ///This function will error if anything in the directory can't be removed.
+    pub fn remove_all(self) -> Result<(), EzError> {
+        let path = self.path.clone();
+        drop(self);
+        Ok(fs::remove_dir_all(path)?)
     }
-    ///Returns a boolean of whether or not the dir is empty, or [`None`] if it hasn't been cached.
-    pub fn is_empty(&self) -> Option<bool> {
-        self.entries.as_ref().map(std::vec::Vec::is_empty)
-    } 
 
-    
     ///Flattens a directory of files and subdirectories to a single vector of files.
     ///Only collects files that have been walked.
     ///```
@@ -157,8 +1320,9 @@ impl EzDir {
         fn collect(dir: EzDir, vec:&mut Vec<EzFile>) {
             for entry in dir {
                 match entry {
-                    EzEntry::File(file) => vec.push(*file),
+                    EzEntry::File(file) => vec.push(file),
                     EzEntry::Dir(dir) => collect(dir, vec),
+                    EzEntry::Symlink(_) => {},
                 }
             }
         }
@@ -169,6 +1333,54 @@ impl EzDir {
 
     }
 
+    ///Collects every cached subdirectory in the tree, depth-first with each directory before its
+    ///contents. This directory isn't included. Each one keeps its own files, while its
+    ///subdirectories, which are returned separately, are left in it uncached. Only collects
+    ///directories that have been walked into.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new(".", false).unwrap();
+    ///dir.walk(2);
+    ///for sub in dir.flatten_dirs() {
+    ///    println!("{}: {:?} entries", sub.path().display(), sub.len());
+    ///}
+    ///```
+    #[must_use] pub fn flatten_dirs(self) -> Vec<EzDir> {
+        //swaps every subdirectory of `dir` for an uncached copy and returns the originals
+        fn detach(dir: &mut EzDir) -> Vec<EzDir> {
+            dir.iter_mut()
+                .filter_map(|entry| match entry {
+                    EzEntry::Dir(d) => {
+                        let mut empty = EzDir::from_parts(d.path.clone(), None);
+                        empty.ignore_hidden = d.ignore_hidden;
+                        Some(std::mem::replace(d, empty))
+                    },
+                    EzEntry::File(_) | EzEntry::Symlink(_) => None,
+                })
+                .collect()
+        }
+
+        let mut root = self;
+        let mut stack = detach(&mut root);
+        stack.reverse();
+        let mut dirs = vec![];
+        while let Some(mut dir) = stack.pop() {
+            stack.extend(detach(&mut dir).into_iter().rev());
+            if dir.total.is_some() {
+                dir.count_total();
+            }
+            dirs.push(dir);
+        }
+        dirs
+    }
+
+    ///Returns references to every cached subdirectory in the tree, in the same order as
+    ///[`EzDir::flatten_dirs`] but without consuming anything. This directory isn't included.
+    #[must_use] pub fn dirs(&self) -> Vec<&EzDir> {
+        self.entries_recursive().filter_map(EzEntry::as_dir).collect()
+    }
+
     ///Flattens a directory of files and subdirectories to a single vector of files.
     ///Collects ALL files, including those that have not been scanned yet.
     ///```
@@ -186,6 +1398,346 @@ impl EzDir {
         dir.flatten()
     }
 
+    ///Like [`EzDir::flatten_all`], but walks with [`EzDir::walk_parallel`] and gathers the files
+    ///on the rayon thread pool. Returns the same files, in the same order. Requires the `rayon`
+    ///feature.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let dir = EzDir::new("src", false).unwrap();
+    ///let files = dir.flatten_parallel();
+    ///assert!(files.iter().any(|f| f.file_name() == Some("lib.rs")));
+    ///```
+    #[cfg(feature = "rayon")]
+    #[must_use] pub fn flatten_parallel(self) -> Vec<EzFile> {
+        use rayon::prelude::*;
+
+        fn collect(dir: EzDir) -> Vec<EzFile> {
+            dir.entries.unwrap_or_default().into_par_iter()
+                .flat_map(|entry| match entry {
+                    EzEntry::File(file) => vec![file],
+                    EzEntry::Dir(dir) => collect(dir),
+                    EzEntry::Symlink(_) => vec![],
+                })
+                .collect()
+        }
+
+        let mut dir = self;
+        dir.walk_parallel(0);
+        collect(dir)
+    }
+
+    ///Flattens a directory of files and subdirectories to a single vector of files, walking
+    ///exactly `depth` levels first. Depth works like in [`EzDir::walk`], so 0 collects ALL
+    ///files, and anything cached deeper than `depth` is left out.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    /////files in this directory and its immediate subdirectories
+    ///let dir = EzDir::new(".", false).unwrap();
+    ///let files = dir.flatten_depth(1);
+    ///```
+    #[must_use] pub fn flatten_depth(self, depth: usize) -> Vec<EzFile> {
+        let mut dir = self;
+        dir.walk(depth);
+        dir.flatten()
+    }
+
+}
+
+//depth-first entry iterator, keeping a stack of the directories being traversed
+struct Entries<'a> {
+    stack: Vec<std::slice::Iter<'a, EzEntry>>,
+}
+impl<'a> Iterator for Entries<'a> {
+    type Item = &'a EzEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(entry) => {
+                    if let EzEntry::Dir(dir) = entry {
+                        self.stack.push(dir.iter());
+                    }
+                    return Some(entry)
+                },
+                None => { self.stack.pop(); },
+            }
+        }
+    }
+}
+
+//depth-first file iterators, same as `Entries` but only yielding files
+struct Files<'a> {
+    stack: Vec<std::slice::Iter<'a, EzEntry>>,
+}
+impl<'a> Iterator for Files<'a> {
+    type Item = &'a EzFile;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(EzEntry::File(file)) => return Some(file),
+                Some(EzEntry::Dir(dir)) => self.stack.push(dir.iter()),
+                Some(EzEntry::Symlink(_)) => {},
+                None => { self.stack.pop(); },
+            }
+        }
+    }
+}
+struct FilesMut<'a> {
+    stack: Vec<std::slice::IterMut<'a, EzEntry>>,
+}
+impl<'a> Iterator for FilesMut<'a> {
+    type Item = &'a mut EzFile;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(EzEntry::File(file)) => return Some(file),
+                Some(EzEntry::Dir(dir)) => self.stack.push(dir.iter_mut_counted()),
+                Some(EzEntry::Symlink(_)) => {},
+                None => { self.stack.pop(); },
+            }
+        }
+    }
+}
+
+//state for `EzDir::walk_iter`, holding an open handle for every directory being read
+struct WalkIter {
+    root: Option<PathBuf>,
+    stack: Vec<fs::ReadDir>,
+    skip_hidden: bool,
+}
+impl Iterator for WalkIter {
+    type Item = Result<EzFile, EzError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            match fs::read_dir(root) {
+                Ok(read) => self.stack.push(read),
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+
+        loop {
+            let entry = match self.stack.last_mut()?.next() {
+                Some(Ok(entry)) if self.skip_hidden && hidden(&entry.file_name(), || entry.metadata()) => continue,
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => return Some(Err(e.into())),
+                None => { self.stack.pop(); continue },
+            };
+            match entry.file_type() {
+                Ok(t) if t.is_file() => return Some(EzFile::open(entry.path())),
+                Ok(t) if t.is_dir() => match fs::read_dir(entry.path()) {
+                    Ok(read) => self.stack.push(read),
+                    Err(e) => return Some(Err(e.into())),
+                },
+                Ok(_) => {},
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+//state for `EzDir::walk_chunked`, pending directories are stored as index paths from the root
+struct WalkChunks<'a> {
+    root: &'a mut EzDir,
+    pending: Vec<(Vec<usize>, usize)>,
+    batch: usize,
+    max: usize,
+}
+impl Iterator for WalkChunks<'_> {
+    type Item = Result<(), EzError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pending.is_empty() {
+            return None
+        }
+
+        for _ in 0..self.batch {
+            let Some((indices, level)) = self.pending.pop() else { break };
+            let mut dir = &mut *self.root;
+            for &i in &indices {
+                match dir.get_mut(i) {
+                    Some(EzEntry::Dir(d)) => dir = d,
+                    _ => unreachable!("pending paths always point at directories"),
+                }
+            }
+
+            match dir.scan() {
+                Ok(entries) => dir.entries = Some(entries),
+                Err(e) => {
+                    if self.pending.is_empty() {
+                        self.root.count_total();
+                    }
+                    return Some(Err(e.into()))
+                },
+            }
+            if level < self.max {
+                for (i, entry) in dir.iter().enumerate() {
+                    if entry.is_dir() {
+                        let mut child = indices.clone();
+                        child.push(i);
+                        self.pending.push((child, level + 1));
+                    }
+                }
+            }
+        }
+
+        if self.pending.is_empty() {
+            self.root.count_total();
+        }
+        Some(Ok(()))
+    }
+}
+
+//compares the contents of two files byte by byte
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    use io::BufRead;
+
+    let mut a = io::BufReader::new(fs::File::open(a)?);
+    let mut b = io::BufReader::new(fs::File::open(b)?);
+    loop {
+        let (x, y) = (a.fill_buf()?, b.fill_buf()?);
+        if x.is_empty() || y.is_empty() {
+            return Ok(x.is_empty() && y.is_empty())
+        }
+        let n = x.len().min(y.len());
+        if x[..n] != y[..n] {
+            return Ok(false)
+        }
+        a.consume(n);
+        b.consume(n);
+    }
+}
+
+//reads a directory from disk, skipping anything that can't be represented
+fn scan(path: &Path, skip_hidden: bool) -> io::Result<Vec<EzEntry>> {
+    Ok(fs::read_dir(path)?
+        .filter_map(|e| e.ok().filter(|e| !(skip_hidden && hidden(&e.file_name(), || e.metadata()))))
+        .filter_map(|e| EzEntry::try_from(e).ok())
+        .collect())
+}
+
+//dotfiles everywhere, plus the hidden attribute on windows, where `metadata` is read to check it
+#[cfg_attr(not(windows), allow(unused_variables))]
+pub(crate) fn hidden(name: &std::ffi::OsStr, metadata: impl FnOnce() -> io::Result<fs::Metadata>) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if metadata().is_ok_and(|m| m.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0) {
+            return true
+        }
+    }
+    name.as_encoded_bytes().starts_with(b".")
+}
+
+//atomically exchanges two paths
+#[cfg(target_os = "linux")]
+fn exchange(a: &Path, b: &Path) -> io::Result<()> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let a_c = CString::new(a.as_os_str().as_bytes())?;
+    let b_c = CString::new(b.as_os_str().as_bytes())?;
+    //SAFETY: both pointers come from live CStrings
+    let ret = unsafe {
+        libc::syscall(libc::SYS_renameat2, libc::AT_FDCWD, a_c.as_ptr(), libc::AT_FDCWD, b_c.as_ptr(), libc::RENAME_EXCHANGE)
+    };
+    if ret == 0 {
+        return Ok(())
+    }
+
+    let err = io::Error::last_os_error();
+    match err.raw_os_error() {
+        //old kernel or a filesystem without exchange support
+        Some(libc::EINVAL | libc::ENOSYS) => exchange_fallback(a, b),
+        _ => Err(err),
+    }
+}
+#[cfg(not(target_os = "linux"))]
+fn exchange(a: &Path, b: &Path) -> io::Result<()> {
+    exchange_fallback(a, b)
+}
+
+//non-atomic exchange through a temporary name, undoing what it can on failure
+fn exchange_fallback(a: &Path, b: &Path) -> io::Result<()> {
+    //`as_path` drops any trailing separator, so the suffix lands on the name
+    let mut tmp = a.components().as_path().as_os_str().to_owned();
+    tmp.push(".ez_swap");
+    fs::rename(a, &tmp)?;
+    if let Err(e) = fs::rename(b, a) {
+        let _ = fs::rename(&tmp, a);
+        return Err(e)
+    }
+    if let Err(e) = fs::rename(&tmp, b) {
+        let _ = fs::rename(a, b);
+        let _ = fs::rename(&tmp, a);
+        return Err(e)
+    }
+    Ok(())
+}
+
+//recreates the symlink at `link` as `to`, pointing at the same target
+pub(crate) fn copy_symlink(link: &Path, to: &Path) -> io::Result<()> {
+    let target = fs::read_link(link)?;
+    #[cfg(unix)]
+    return std::os::unix::fs::symlink(target, to);
+    #[cfg(windows)]
+    return if fs::metadata(link).is_ok_and(|m| m.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, to)
+    } else {
+        std::os::windows::fs::symlink_file(target, to)
+    };
+    #[cfg(not(any(unix, windows)))]
+    return Err(io::Error::new(io::ErrorKind::Unsupported, format!("Can't create symlink {}", to.display())));
+}
+
+//final component of an entry's path
+fn entry_name(entry: &EzEntry) -> std::borrow::Cow<'_, str> {
+    let path = entry.path();
+    path.file_name().map_or(path.to_string_lossy(), |n| n.to_string_lossy())
+}
+
+//compares strings with embedded numbers by value, "file2" < "file10"
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    //splits off the leading run of digits or non digits
+    fn run(s: &str) -> (&str, &str) {
+        let digit = s.starts_with(|c: char| c.is_ascii_digit());
+        let end = s.find(|c: char| c.is_ascii_digit() != digit).unwrap_or(s.len());
+        s.split_at(end)
+    }
+
+    let (mut a, mut b) = (a, b);
+    while !a.is_empty() && !b.is_empty() {
+        let ((run_a, rest_a), (run_b, rest_b)) = (run(a), run(b));
+        let both_digits = run_a.starts_with(|c: char| c.is_ascii_digit()) && run_b.starts_with(|c: char| c.is_ascii_digit());
+        let ord = if both_digits {
+            let (num_a, num_b) = (run_a.trim_start_matches('0'), run_b.trim_start_matches('0'));
+            num_a.len().cmp(&num_b.len())
+                .then_with(|| num_a.cmp(num_b))
+                .then_with(|| run_a.len().cmp(&run_b.len()))
+        } else {
+            run_a.cmp(run_b)
+        };
+        if ord != Ordering::Equal {
+            return ord
+        }
+        (a, b) = (rest_a, rest_b);
+    }
+    a.len().cmp(&b.len())
+}
+
+//path of `path` relative to `root`, joined with `/` so it can be glob matched
+pub(crate) fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
 //just so i can generate an empty iterator
@@ -217,6 +1769,7 @@ impl<'i> IntoIterator for &'i mut EzDir {
     //hey whatever works
     #[allow(const_item_mutation)]
     fn into_iter(self) -> Self::IntoIter {
+        self.total = None;
         self.entries.as_deref_mut().map_or(EMPTY_ITER.iter_mut(), <[EzEntry]>::iter_mut)
     }
 }
@@ -224,14 +1777,67 @@ impl<'i> IntoIterator for &'i mut EzDir {
 
 
 
+///The result of [`EzDir::diff`], holding paths relative to each directory's root.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DirDiff {
+    ///Entries only in the directory `diff` was called on.
+    pub only_in_self: Vec<String>,
+    ///Entries only in the directory passed to `diff`.
+    pub only_in_other: Vec<String>,
+    ///Entries in both.
+    pub in_both: Vec<String>,
+}
+
 #[derive(Debug)]
 ///Represents an entry in a directory.
+///
+///Two entries are equal if they are the same kind and have the same path. Like with [`EzFile`]
+///and [`EzDir`], contents aren't compared.
 pub enum EzEntry {
-    File(Box<EzFile>),
+    File(EzFile),
     Dir(EzDir),
+    ///A symbolic link, holding the path of the link itself. Links aren't followed, so walking
+    ///never loops on a link pointing back up the tree. Use [`std::fs::read_link`] for the target.
+    Symlink(PathBuf),
 }
 
 impl EzEntry {
+    /// Opens whatever is at `path` as the matching entry, for code that accepts either a file
+    /// or a directory. Like when scanning, a symlink isn't followed and gives [`Symlink`], and
+    /// a directory is returned uncached.
+    ///
+    /// [`Symlink`]: EzEntry::Symlink
+    /// ```
+    /// use ez_fs::EzEntry;
+    ///
+    /// assert!(EzEntry::open("Cargo.toml").unwrap().is_file());
+    /// assert!(EzEntry::open("src").unwrap().is_dir());
+    /// ```
+    /// # Errors
+    /// This function will error with [`EzError::Io`] if `path` does not exist or can't be
+    /// opened, or [`EzError::UnsupportedFileType`] if it's something else, like a socket.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, EzError> {
+        let path = path.as_ref();
+        Self::from_type(path.to_owned(), fs::symlink_metadata(path)?.file_type())
+    }
+
+    fn from_type(path: PathBuf, file_type: fs::FileType) -> Result<Self, EzError> {
+        //this looks like a mess
+        if file_type.is_file() {
+            return Ok(Self::File(EzFile::open(path)?))
+        }
+        
+        if file_type.is_dir() {
+            return Ok(Self::Dir(EzDir::new(path, false)?))
+        }
+
+        if file_type.is_symlink() {
+            return Ok(Self::Symlink(path))
+        }
+
+        Err(EzError::UnsupportedFileType(path))
+    }
+
     /// Returns `true` if the entry is [`File`].
     ///
     /// [`File`]: EzEntry::File
@@ -247,38 +1853,182 @@ impl EzEntry {
     pub fn is_dir(&self) -> bool {
         matches!(self, Self::Dir(..))
     }
+
+    /// Returns `true` if the entry is [`Symlink`].
+    ///
+    /// [`Symlink`]: EzEntry::Symlink
+    #[must_use]
+    pub fn is_symlink(&self) -> bool {
+        matches!(self, Self::Symlink(..))
+    }
+
+    /// Returns `true` if the entry is hidden: its name starts with a `.`, or on Windows it has
+    /// the hidden attribute. Same rules as [`EzDir::set_ignore_hidden`].
+    #[must_use]
+    pub fn is_hidden(&self) -> bool {
+        match self {
+            Self::File(file) => file.is_hidden(),
+            Self::Dir(EzDir { path, .. }) | Self::Symlink(path) => {
+                path.file_name().is_some_and(|name| hidden(name, || fs::symlink_metadata(path)))
+            },
+        }
+    }
+
+    /// Returns the inner file if the entry is [`File`], otherwise [`None`].
+    ///
+    /// [`File`]: EzEntry::File
+    #[must_use]
+    pub fn as_file(&self) -> Option<&EzFile> {
+        match self {
+            Self::File(file) => Some(file),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner file mutably if the entry is [`File`], otherwise [`None`].
+    ///
+    /// [`File`]: EzEntry::File
+    pub fn as_file_mut(&mut self) -> Option<&mut EzFile> {
+        match self {
+            Self::File(file) => Some(file),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner directory if the entry is [`Dir`], otherwise [`None`].
+    ///
+    /// [`Dir`]: EzEntry::Dir
+    #[must_use]
+    pub fn as_dir(&self) -> Option<&EzDir> {
+        match self {
+            Self::Dir(dir) => Some(dir),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner directory mutably if the entry is [`Dir`], otherwise [`None`].
+    ///
+    /// [`Dir`]: EzEntry::Dir
+    pub fn as_dir_mut(&mut self) -> Option<&mut EzDir> {
+        match self {
+            Self::Dir(dir) => Some(dir),
+            _ => None,
+        }
+    }
+
+    /// Converts the entry into its inner file if it is [`File`], otherwise [`None`].
+    ///
+    /// [`File`]: EzEntry::File
+    #[must_use]
+    pub fn into_file(self) -> Option<EzFile> {
+        match self {
+            Self::File(file) => Some(file),
+            _ => None,
+        }
+    }
+
+    /// Converts the entry into its inner directory if it is [`Dir`], otherwise [`None`].
+    ///
+    /// [`Dir`]: EzEntry::Dir
+    #[must_use]
+    pub fn into_dir(self) -> Option<EzDir> {
+        match self {
+            Self::Dir(dir) => Some(dir),
+            _ => None,
+        }
+    }
+
+    fn rebase(&mut self, from: &Path, to: &Path) {
+        match self {
+            Self::File(file) => file.rebase(from, to),
+            Self::Dir(dir) => dir.rebase(from, to),
+            Self::Symlink(path) => if let Ok(rest) = path.strip_prefix(from) {
+                *path = to.join(rest);
+            },
+        }
+    }
+
+    /// Returns the path of the entry, whichever variant it is.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::File(file) => file.path(),
+            Self::Dir(dir) => dir.path(),
+            Self::Symlink(path) => path,
+        }
+    }
+
+    /// Returns the final component of the entry's path, or [`None`] if it isn't valid UTF-8.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.path().file_name()?.to_str()
+    }
 }
 
 impl TryFrom<fs::DirEntry> for EzEntry {
-    type Error = io::Error;
+    type Error = EzError;
 
     fn try_from(value: fs::DirEntry) -> Result<Self, Self::Error> {
-        let file_type = value.file_type()?;
-        //this looks like a mess
-        if file_type.is_file() {return 
-            Ok(
-                Self::File(
-                    Box::new(EzFile::open(
-                        value.path().to_str().ok_or(io::Error::new(io::ErrorKind::Other, "Error converting path"))?
-                        )?)
-                )
-            )
-        }
-        
-        if file_type.is_dir() {
-            return Ok(Self::Dir(EzDir::new(value.path().to_str().ok_or(io::Error::new(io::ErrorKind::Other, "Error converting path"))?, false)?))
-        }
+        Self::from_type(value.path(), value.file_type()?)
+    }
+}
+
+///Indexes into the cached entries, like [`EzDir::get`] but panicking instead of returning
+///[`None`].
+///```
+///use ez_fs::EzDir;
+///
+///let dir = EzDir::new(".", true).unwrap();
+///println!("{}", dir[0]);
+///```
+///# Panics
+///Panics if the directory hasn't been cached or `idx` is out of bounds.
+impl std::ops::Index<usize> for EzDir {
+    type Output = EzEntry;
+
+    fn index(&self, idx: usize) -> &Self::Output {
+        let Some(entries) = &self.entries else {
+            panic!("directory {} has not been cached", self.path.display())
+        };
+        &entries[idx]
+    }
+}
+impl std::ops::IndexMut<usize> for EzDir {
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        self.total = None;
+        let Some(entries) = &mut self.entries else {
+            panic!("directory {} has not been cached", self.path.display())
+        };
+        &mut entries[idx]
+    }
+}
 
-        Err(io::Error::new(io::ErrorKind::Other, "Invalid file type (likely simlink)"))
+impl PartialEq for EzDir {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+impl Eq for EzDir {}
+
+impl PartialEq for EzEntry {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::File(a), Self::File(b)) => a == b,
+            (Self::Dir(a), Self::Dir(b)) => a == b,
+            (Self::Symlink(a), Self::Symlink(b)) => a == b,
+            _ => false,
+        }
     }
 }
+impl Eq for EzEntry {}
 
 impl Display for EzDir {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for entry in self {
             match entry {
-                EzEntry::File(file) => writeln!(f, "{}", file.path())?,
-                EzEntry::Dir(dir) => write!(f, "{}\n{}", dir.path(), dir)?,
+                EzEntry::File(file) => writeln!(f, "{file}")?,
+                EzEntry::Dir(dir) => write!(f, "{}\n{}", dir.path.display(), dir)?,
+                EzEntry::Symlink(path) => writeln!(f, "{}", path.display())?,
             }
         }
         Ok(())
@@ -288,8 +2038,9 @@ impl Display for EzDir {
 impl Display for EzEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::File(file) => write!(f, "{}", file.path()),
-            Self::Dir(dir) => write!(f, "{}", dir.path()),
+            Self::File(file) => write!(f, "{file}"),
+            Self::Dir(dir) => write!(f, "{}", dir.path.display()),
+            Self::Symlink(path) => write!(f, "{}", path.display()),
         }
     }
 }
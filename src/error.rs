@@ -0,0 +1,74 @@
+use std::{io, fmt::Display, path::PathBuf};
+
+///Errors raised by `ez_fs` itself, so callers can tell its failure modes apart from each
+///other and from plain I/O failures.
+///
+///Every fallible function in the crate returns it. It converts into an [`io::Error`] with `?`,
+///for callers that only deal in those, and the trait impls such as [`io::Read`] that have to
+///return one wrap the `EzError`, which can be recovered with [`io::Error::get_ref`] and
+///`downcast_ref`.
+///```
+///use ez_fs::{EzDir, EzError};
+///
+///match EzDir::new("Cargo.toml", false) {
+///    Err(EzError::NotADirectory(path)) => println!("{} is a file", path.display()),
+///    Err(e) => panic!("{e}"),
+///    Ok(_) => unreachable!(),
+///}
+///```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EzError {
+    ///The path exists but isn't a directory.
+    NotADirectory(PathBuf),
+    ///The entry is not a file, directory or symlink, such as a socket or pipe.
+    UnsupportedFileType(PathBuf),
+    ///An underlying I/O operation failed.
+    Io(io::Error),
+}
+
+impl EzError {
+    ///Returns the closest [`io::ErrorKind`], which is also the kind it gets when converted into
+    ///an [`io::Error`].
+    #[must_use] pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::NotADirectory(_) => io::ErrorKind::NotADirectory,
+            Self::UnsupportedFileType(_) => io::ErrorKind::Unsupported,
+            Self::Io(e) => e.kind(),
+        }
+    }
+}
+
+impl Display for EzError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotADirectory(path) => write!(f, "{} is not a directory", path.display()),
+            Self::UnsupportedFileType(path) => write!(f, "{} is not a file, directory or symlink", path.display()),
+            Self::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for EzError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for EzError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<EzError> for io::Error {
+    fn from(value: EzError) -> Self {
+        match value {
+            EzError::Io(e) => e,
+            other => io::Error::new(other.kind(), other),
+        }
+    }
+}
@@ -1,15 +1,74 @@
-use std::{io, time::SystemTime, fmt::Display};
+use crate::error::EzError;
+use std::{io::{self, BufRead, Read, Seek, SeekFrom, Write}, fs, path::{Path, PathBuf}, time::SystemTime, fmt::Display, ops::{Deref, DerefMut}};
 
+const TAKEN: &str = "the handle is only taken by `EzFile::into_raw`, which consumes the file";
+
+
+///What [`EzFile::lines_capped`] does with a line longer than its cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongLines {
+    ///Yield the line in several pieces, marking all but the last as [`CappedLine::truncated`].
+    Split,
+    ///Yield an [`io::ErrorKind::InvalidData`] error and stop.
+    Error,
+}
+
+///A line, or a piece of one, read by [`EzFile::lines_capped`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CappedLine {
+    pub text: String,
+    ///`true` if the line was cut at the cap and continues in the next piece.
+    pub truncated: bool,
+}
+
+///Controls when an [`EzFile`] forces its writes to disk, trading durability for speed.
+///Syncing is only meaningful for handles that have been written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncPolicy {
+    ///Never sync, leaving it up to the OS. Fastest, but recent writes can be lost on a crash.
+    #[default]
+    Never,
+    ///Sync once when the file is dropped. Cheap for many small writes, but errors are ignored
+    ///since `Drop` can't return them.
+    OnDrop,
+    ///Sync on every [`flush`](std::io::Write::flush) as well as on drop. Safest, but every
+    ///flush waits on the disk.
+    Always,
+}
 
 ///Representation of an open file.
 ///Wraps [`std::fs`] things such as metadata together to handle neatly.
+///
+///Two files are equal if they have the same path, whatever their contents or handles.
+///
+///Files are [`Send`] and [`Sync`]. Reading or writing needs `&mut`, so sharing one between
+///threads takes a lock; for independent readers use [`EzFile::try_clone`] instead.
+///
+///Dropping a file flushes it, and syncs it depending on its [`SyncPolicy`]. Errors are ignored
+///since `Drop` can't return them, so call [`flush`](std::io::Write::flush) first to handle them.
 #[derive(Debug)]
 pub struct EzFile {
-    path: String,
-    handle: std::fs::File,
+    path: PathBuf,
+    //only `None` once `into_raw` has taken it, which consumes the file
+    handle: Option<std::fs::File>,
     metadata: std::fs::Metadata,
+    sync: SyncPolicy,
+    //set for handles known to be in append mode, so `append_bytes` can skip reopening
+    append: bool,
 }
 impl EzFile {
+    ///Returns `true` if `path` exists and is a file, following symlinks. Cheaper than opening it
+    ///just to check, but the file may still be gone by the time it's opened.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///assert!(EzFile::exists("Cargo.toml"));
+    ///assert!(!EzFile::exists("src"));
+    ///```
+    #[must_use] pub fn exists(path: impl AsRef<Path>) -> bool {
+        path.as_ref().is_file()
+    }
+
     ///Open a file in read-only mode.
     ///Refer to [`std::io::Read`] for more information.
     ///```
@@ -22,15 +81,100 @@ impl EzFile {
     ///```
     ///# Errors
     ///This function will error if `path` does not exist.
-    pub fn open(path: &str) -> io::Result<Self> {
-        let handle = std::fs::File::open(path)?;
-        let metadata = handle.metadata()?;
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, EzError> {
+        let path = path.as_ref();
+        Self::from_handle(path, std::fs::File::open(path)?)
+    }
+    ///Like [`EzFile::open`], but retries up to `attempts` times in total when the file is
+    ///temporarily locked by another process, waiting `backoff` before the first retry and
+    ///doubling the wait after each one.
+    ///
+    ///On Windows this covers sharing and lock violations, which happen when a scanner or editor
+    ///briefly holds the file open, as well as [`io::ErrorKind::PermissionDenied`]. Elsewhere
+    ///only `PermissionDenied` is retried. Any other error is returned right away.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::time::Duration;
+    ///
+    ///let file = EzFile::open_with_retry("foo.txt", 3, Duration::from_millis(50));
+    ///```
+    ///# Errors
+    ///This function will error with the last error if every attempt fails.
+    pub fn open_with_retry(path: impl AsRef<Path>, attempts: usize, backoff: std::time::Duration) -> Result<Self, EzError> {
+        //32 and 33 are ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION on windows
+        fn locked(e: &io::Error) -> bool {
+            e.kind() == io::ErrorKind::PermissionDenied || (cfg!(windows) && matches!(e.raw_os_error(), Some(32 | 33)))
+        }
+
+        Self::open_retrying(path.as_ref(), attempts, backoff, locked)
+    }
+    ///Like [`EzFile::open`], but retries up to `retries` more times on errors that are usually
+    ///transient, such as on network filesystems: [`io::ErrorKind::Interrupted`],
+    ///[`io::ErrorKind::WouldBlock`] and [`io::ErrorKind::TimedOut`]. Waits 10ms before the first
+    ///retry and doubles the wait after each one. Any other error is returned right away. For
+    ///files locked by another process, see [`EzFile::open_with_retry`].
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let file = EzFile::open_with_retries("foo.txt", 3);
+    ///```
+    ///# Errors
+    ///This function will error with the last error if every attempt fails.
+    pub fn open_with_retries(path: impl AsRef<Path>, retries: usize) -> Result<Self, EzError> {
+        fn transient(e: &io::Error) -> bool {
+            matches!(e.kind(), io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+        }
+
+        let backoff = std::time::Duration::from_millis(10);
+        Self::open_retrying(path.as_ref(), retries.saturating_add(1), backoff, transient)
+    }
+    //opens `path`, retrying errors `retry` accepts with exponential backoff
+    fn open_retrying(path: &Path, attempts: usize, backoff: std::time::Duration, retry: fn(&io::Error) -> bool) -> Result<Self, EzError> {
+        let mut delay = backoff;
+        let mut attempt = 1;
+        loop {
+            match std::fs::File::open(path) {
+                Err(e) if retry(&e) && attempt < attempts => {
+                    std::thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                },
+                result => return Self::from_handle(path, result?),
+            }
+        }
+    }
+
+    ///Opens this file again as a new read-only handle with its own cursor, starting at the
+    ///beginning. Reads on one don't move the other, so each thread can get its own. For a
+    ///handle that shares the cursor, see [`EzFile::try_clone_handle`].
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let file = EzFile::open("Cargo.toml").unwrap();
+    ///let mut clone = file.try_clone().unwrap();
+    ///assert!(clone.read_all_string().unwrap().contains("[package]"));
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be opened again, for example if it was removed.
+    pub fn try_clone(&self) -> Result<EzFile, EzError> {
+        Self::open(&self.path)
+    }
+
+    ///Duplicates the underlying OS handle, see [`std::fs::File::try_clone`]. Unlike
+    ///[`EzFile::try_clone`], both handles share one cursor and access mode, so a read or seek on
+    ///either moves both.
+    ///# Errors
+    ///This function will error if the handle can't be duplicated.
+    pub fn try_clone_handle(&self) -> Result<EzFile, EzError> {
         Ok(Self {
-            path: path.to_string(),
-            handle,
-            metadata,
+            path: self.path.clone(),
+            handle: Some(self.handle().try_clone()?),
+            metadata: self.metadata.clone(),
+            sync: self.sync,
+            append: self.append,
         })
     }
+
     ///Open a file in write-only mode.
     ///Refer to [`std::io::Write`] for more information.
     ///```
@@ -42,41 +186,813 @@ impl EzFile {
     ///```
     ///# Errors
     ///This function will error if `path` does not exist.
-    pub fn create(path: &str) -> io::Result<Self> {
-        let handle = std::fs::File::create(path)?;
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, EzError> {
+        let path = path.as_ref();
+        Self::from_handle(path, std::fs::File::create(path)?)
+    }
+
+    ///Open a file in append mode, creating it if it doesn't exist. Every write lands at the end
+    ///of the file, so existing contents are never overwritten.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::append("foo_log.txt").unwrap();
+    ///file.write_all(b"started\n").unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be opened or created.
+    pub fn append(path: impl AsRef<Path>) -> Result<Self, EzError> {
+        let path = path.as_ref();
+        let mut file = Self::from_handle(path, fs::OpenOptions::new().append(true).create(true).open(path)?)?;
+        file.append = true;
+        Ok(file)
+    }
+
+    ///Open an existing file for both reading and writing.
+    ///
+    ///Reads and writes share a single cursor: reading 4 bytes and then writing 2 overwrites
+    ///bytes 4 and 5 in place, and the next read starts after them. Nothing is truncated, so
+    ///writing fewer bytes than the file holds leaves the rest untouched.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::{Read, Write};
+    ///
+    ///std::fs::write("foo_rw.txt", "abcdef").unwrap();
+    ///let mut file = EzFile::open_rw("foo_rw.txt").unwrap();
+    ///let mut head = [0; 2];
+    ///file.read_exact(&mut head).unwrap();
+    ///file.write_all(b"XY").unwrap();
+    ///assert_eq!(std::fs::read_to_string("foo_rw.txt").unwrap(), "abXYef");
+    ///```
+    ///# Errors
+    ///This function will error if `path` does not exist.
+    pub fn open_rw(path: impl AsRef<Path>) -> Result<Self, EzError> {
+        let path = path.as_ref();
+        Self::from_handle(path, fs::OpenOptions::new().read(true).write(true).open(path)?)
+    }
+
+    ///Opens a file with caller-supplied [`fs::OpenOptions`], for combinations the other
+    ///constructors don't cover, like failing if the file already exists.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::fs::OpenOptions;
+    ///
+    ///let file = EzFile::with_options("foo_options.txt", OpenOptions::new().read(true).append(true).create(true));
+    ///assert!(file.is_ok());
+    ///```
+    ///# Errors
+    ///This function will error if `path` can't be opened with `options`.
+    pub fn with_options(path: impl AsRef<Path>, options: &fs::OpenOptions) -> Result<Self, EzError> {
+        let path = path.as_ref();
+        Self::from_handle(path, options.open(path)?)
+    }
+
+    fn from_handle(path: &Path, handle: std::fs::File) -> Result<Self, EzError> {
         let metadata = handle.metadata()?;
         Ok(Self {
-            path: path.to_string(),
-            handle,
+            path: path.to_owned(),
+            handle: Some(handle),
             metadata,
+            sync: SyncPolicy::default(),
+            append: false,
         })
     }
 
-    
+    ///Open a file in read-only mode with `O_DIRECT`, bypassing the OS page cache. Meant for
+    ///workloads that do their own caching, such as databases.
+    ///
+    ///Direct I/O is strict: the buffer address, its length and the file offset of every read
+    ///must be aligned to the filesystem's logical block size (usually 512 or 4096 bytes),
+    ///otherwise reads fail with `EINVAL`. Some filesystems such as tmpfs don't support it at all.
+    ///Switching modes with [`EzFile::to_write`] or [`EzFile::to_read`] reopens the file without
+    ///`O_DIRECT`.
+    ///An easy way to get an aligned buffer is a block type with a forced alignment:
+    ///```no_run
+    ///use ez_fs::EzFile;
+    ///use std::io::Read;
+    ///
+    ///#[repr(C, align(4096))]
+    ///struct Block([u8; 4096]);
+    ///
+    ///let mut file = EzFile::open_direct("foo.txt").unwrap();
+    ///let mut block = Block([0; 4096]);
+    ///let read = file.read(&mut block.0).unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if `path` does not exist or the filesystem doesn't support
+    ///direct I/O.
+    #[cfg(target_os = "linux")]
+    pub fn open_direct(path: impl AsRef<Path>) -> Result<Self, EzError> {
+        use std::os::unix::fs::OpenOptionsExt;
 
-    ///Deconstructs and returns the path, file handle, and metadata of a file.
-    #[must_use] pub fn into_raw(self) -> (String, std::fs::File, std::fs::Metadata) {
-        (self.path, self.handle, self.metadata)
+        let path = path.as_ref();
+        Self::from_handle(path, fs::OpenOptions::new().read(true).custom_flags(libc::O_DIRECT).open(path)?)
+    }
+
+    ///Creates `path` with `contents` and returns a read-only handle positioned at the start,
+    ///with metadata reflecting the written file.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Read;
+    ///
+    ///let mut file = EzFile::write_then_read("foo_then_read.txt", b"bar").unwrap();
+    ///let mut buf = String::new();
+    ///file.read_to_string(&mut buf).unwrap();
+    ///assert_eq!(buf, "bar");
+    ///```
+    ///# Errors
+    ///This function will error if `path` can't be written to or reopened.
+    pub fn write_then_read(path: impl AsRef<Path>, contents: &[u8]) -> Result<EzFile, EzError> {
+        let mut file = Self::create(path)?;
+        file.write_all(contents)?;
+        file.flush()?;
+        file.to_read()?;
+        file.refresh_metadata()?;
+        Ok(file)
     }
 
+    ///Creates a write-only handle that stages everything in a hidden temporary file next to
+    ///`path`, and only replaces `path` when [`AtomicEzFile::commit`] is called. If the handle is
+    ///dropped without committing, including during a panic, the temporary file is removed and
+    ///`path` is left untouched, so readers never see a half-written file.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::{Read, Write};
+    ///
+    ///let mut staged = EzFile::create_atomic("foo_atomic.txt").unwrap();
+    ///staged.write_all(b"bar").unwrap();
+    ///let mut file = staged.commit().unwrap();
+    ///
+    ///let mut buf = String::new();
+    ///file.read_to_string(&mut buf).unwrap();
+    ///assert_eq!(buf, "bar");
+    ///```
+    ///# Errors
+    ///This function will error if the temporary file can't be created.
+    pub fn create_atomic(path: impl AsRef<Path>) -> Result<AtomicEzFile, EzError> {
+        let path = path.as_ref();
+        Ok(AtomicEzFile {
+            file: Self::create(temp_sibling(path))?,
+            target: path.to_owned(),
+            done: false,
+        })
+    }
+
+    ///Atomically replaces `path` with `contents`, through [`EzFile::create_atomic`]. Readers see
+    ///either the old file or the whole new one, never a partial write, and if anything fails the
+    ///temporary file is removed. The replace is only atomic within one filesystem, which always
+    ///holds here since the temporary file sits next to `path`. An existing file keeps its
+    ///permissions. Returns a read-only handle to the new file.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::write_atomic("foo_write_atomic.txt", b"bar").unwrap();
+    ///assert_eq!(file.read_all_string().unwrap(), "bar");
+    ///```
+    ///# Errors
+    ///This function will error if the temporary file can't be written or renamed over `path`.
+    pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> Result<EzFile, EzError> {
+        let mut staged = Self::create_atomic(path)?;
+        staged.write_all(contents)?;
+        staged.commit()
+    }
+
+    ///Reads the text in `path`, passes it to `f`, and atomically replaces the file with whatever
+    ///`f` returns, keeping its permissions. If `f` panics or anything fails, the original file is
+    ///left untouched. Returns a read-only handle to the edited file.
+    ///```
+    ///use ez_fs::EzFile;
+    ///# std::fs::write("foo_edit.toml", "debug = false\n").unwrap();
+    ///
+    ///EzFile::edit("foo_edit.toml", |text| text.replace("debug = false", "debug = true")).unwrap();
+    ///assert_eq!(std::fs::read_to_string("foo_edit.toml").unwrap(), "debug = true\n");
+    ///```
+    ///# Errors
+    ///This function will error if `path` can't be read, isn't valid UTF-8, or can't be replaced.
+    pub fn edit<F: FnOnce(&str) -> String>(path: impl AsRef<Path>, f: F) -> Result<EzFile, EzError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let edited = f(&text);
+
+        //`commit` carries the permissions over
+        let mut staged = Self::create_atomic(path)?;
+        staged.write_all(edited.as_bytes())?;
+        staged.commit()
+    }
+
+    ///Opens `path` for appending as a rotating log. Once a write would push it past `max_bytes`,
+    ///`path` is moved to `path.1`, older rotations are shifted up (`path.1` to `path.2` and so on)
+    ///keeping at most `max_files` of them, and writing continues in a fresh `path`.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut log = EzFile::rotating("foo_log.txt", 1024, 3).unwrap();
+    ///writeln!(log, "started").unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if `path` can't be opened for appending.
+    pub fn rotating(path: impl AsRef<Path>, max_bytes: u64, max_files: usize) -> Result<RotatingEzFile, EzError> {
+        let path = path.as_ref();
+        let file = Self::from_handle(path, fs::OpenOptions::new().append(true).create(true).open(path)?)?;
+        Ok(RotatingEzFile {
+            written: file.metadata.len(),
+            file,
+            max_bytes,
+            max_files,
+        })
+    }
+
+    ///Creates or opens a file and takes an exclusive lock on it, returning a guard that
+    ///unlocks and removes the file when dropped. This is the usual pidfile pattern for making
+    ///sure only one instance of a program is running.
+    ///
+    ///Only the lock decides who owns the file, so a pidfile left behind by a process that
+    ///crashed is simply taken over and emptied.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut lock = EzFile::create_locked("foo.pid").unwrap();
+    ///write!(lock, "{}", std::process::id()).unwrap();
+    ///
+    /////a second instance can't take the lock
+    ///assert!(EzFile::create_locked("foo.pid").is_err());
+    ///
+    ///drop(lock);
+    ///assert!(!std::path::Path::new("foo.pid").exists());
+    ///```
+    ///# Errors
+    ///This function will error with [`io::ErrorKind::WouldBlock`] if another process holds the
+    ///lock, or if the file can't be opened, locked or emptied.
+    pub fn create_locked(path: impl AsRef<Path>) -> Result<EzFileGuard, EzError> {
+        let path = path.as_ref();
+        loop {
+            let handle = fs::OpenOptions::new().write(true).create(true).truncate(false).open(path)?;
+            if let Err(e) = handle.try_lock() {
+                return Err(match e {
+                    fs::TryLockError::WouldBlock => io::Error::new(io::ErrorKind::WouldBlock, format!("{} is locked by another process", path.display())),
+                    fs::TryLockError::Error(e) => e,
+                }.into())
+            }
+
+            //the previous owner may have removed the file between our open and lock,
+            //in which case the lock is on a file nobody else can see
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                let (ours, current) = (handle.metadata()?, fs::metadata(path));
+                if !current.is_ok_and(|m| m.dev() == ours.dev() && m.ino() == ours.ino()) {
+                    continue
+                }
+            }
+
+            //only emptied once locked, so a running owner's pid is never wiped
+            handle.set_len(0)?;
+            return Ok(EzFileGuard { file: Self::from_handle(path, handle)? })
+        }
+    }
+
+    ///Copies the whole file into `dest` without buffering it all in memory, returning the number
+    ///of bytes copied. The handle must be readable.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_stream.txt").unwrap();
+    ///file.write_all(b"bar").unwrap();
+    ///file.to_read().unwrap();
+    ///
+    ///let mut out = vec![];
+    ///assert_eq!(file.stream_to(&mut out).unwrap(), 3);
+    ///assert_eq!(out, b"bar");
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be read or `dest` can't be written to.
+    pub fn stream_to<W: Write + ?Sized>(&mut self, dest: &mut W) -> Result<u64, EzError> {
+        self.handle_mut().seek(SeekFrom::Start(0))?;
+        self.stream_rest_to(dest)
+    }
+
+    ///Like [`EzFile::stream_to`], but copies from the current position instead of the start.
+    ///# Errors
+    ///This function will error if the file can't be read or `dest` can't be written to.
+    pub fn stream_rest_to<W: Write + ?Sized>(&mut self, dest: &mut W) -> Result<u64, EzError> {
+        Ok(io::copy(self.handle_mut(), dest)?)
+    }
+
+    ///Reads the whole file into memory, refusing to read more than `max_bytes`. Files that are
+    ///already too large are rejected up front, and the cap is still enforced while reading in
+    ///case the file grows. Useful when handling untrusted input.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_slurp.txt").unwrap();
+    ///file.write_all(b"bar").unwrap();
+    ///file.to_read().unwrap();
+    ///
+    ///assert_eq!(file.slurp_limited(3).unwrap(), b"bar");
+    ///assert!(file.slurp_limited(2).is_err());
+    ///```
+    ///# Errors
+    ///This function will error with [`io::ErrorKind::InvalidData`] if the file is larger than
+    ///`max_bytes`, or if it can't be read.
+    pub fn slurp_limited(&mut self, max_bytes: usize) -> Result<Vec<u8>, EzError> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidData, format!("File is larger than {max_bytes} bytes"));
+
+        let len = self.handle().metadata()?.len();
+        if len > max_bytes as u64 {
+            return Err(too_large().into())
+        }
+
+        self.handle_mut().seek(SeekFrom::Start(0))?;
+        let mut buf = Vec::with_capacity(len as usize);
+        //read one extra byte to tell "exactly max_bytes" apart from "grew past it"
+        (self.handle_mut()).take(max_bytes as u64 + 1).read_to_end(&mut buf)?;
+        if buf.len() > max_bytes {
+            return Err(too_large().into())
+        }
+        Ok(buf)
+    }
+
+    ///Reads the whole file as a string with every line ending turned into `\n`. Both `\r\n`
+    ///(Windows) and lone `\r` (classic Mac) endings are converted. The file on disk is left
+    ///untouched.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_lf.txt").unwrap();
+    ///file.write_all(b"a\r\nb\rc\n").unwrap();
+    ///file.to_read().unwrap();
+    ///
+    ///assert_eq!(file.slurp_lf().unwrap(), "a\nb\nc\n");
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be read or isn't valid UTF-8.
+    pub fn slurp_lf(&mut self) -> Result<String, EzError> {
+        self.handle_mut().seek(SeekFrom::Start(0))?;
+        let mut text = String::new();
+        self.handle_mut().read_to_string(&mut text)?;
+        Ok(text.replace("\r\n", "\n").replace('\r', "\n"))
+    }
+
+    ///Reads the whole file, from the start, into a string.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::write_then_read("foo_read_all.txt", b"bar").unwrap();
+    ///assert_eq!(file.read_all_string().unwrap(), "bar");
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be read or isn't valid UTF-8.
+    pub fn read_all_string(&mut self) -> Result<String, EzError> {
+        self.handle_mut().seek(SeekFrom::Start(0))?;
+        let mut text = String::new();
+        self.handle_mut().read_to_string(&mut text)?;
+        Ok(text)
+    }
+
+    ///Reads the whole file, from the start, into a byte vector.
+    ///# Errors
+    ///This function will error if the file can't be read.
+    pub fn read_all_bytes(&mut self) -> Result<Vec<u8>, EzError> {
+        self.handle_mut().seek(SeekFrom::Start(0))?;
+        let mut bytes = vec![];
+        self.handle_mut().read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    ///Reads up to `buf.len()` bytes starting at `offset` and returns how many were read, like
+    ///[`io::Read::read`]. Only needs `&self`, so several threads can read one file at once.
+    ///
+    ///On Unix the cursor doesn't move. On Windows it's left after the bytes read, so don't mix
+    ///this with cursor-based reads there.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let file = EzFile::write_then_read("foo_read_at.txt", b"header:body").unwrap();
+    ///let mut buf = [0; 4];
+    ///assert_eq!(file.read_at(7, &mut buf).unwrap(), 4);
+    ///assert_eq!(&buf, b"body");
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be read.
+    #[cfg(any(unix, windows))]
+    pub fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<usize, EzError> {
+        #[cfg(unix)]
+        return Ok(std::os::unix::fs::FileExt::read_at(self.handle(), buf, offset)?);
+        #[cfg(windows)]
+        return Ok(std::os::windows::fs::FileExt::seek_read(self.handle(), buf, offset)?);
+    }
+
+    ///Returns an iterator over the lines of the file from the start, read through a buffer.
+    ///Line endings are stripped, like [`BufRead::lines`]. The buffer reads ahead, so the cursor
+    ///ends up past the last line yielded.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::write_then_read("foo_lines.txt", b"a\r\nb\n").unwrap();
+    ///let lines: Vec<_> = file.lines().unwrap().map(Result::unwrap).collect();
+    ///assert_eq!(lines, ["a", "b"]);
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be rewound, and the iterator yields an error
+    ///for any line that can't be read or isn't valid UTF-8.
+    pub fn lines(&mut self) -> Result<impl Iterator<Item = Result<String, EzError>> + '_, EzError> {
+        self.handle_mut().seek(SeekFrom::Start(0))?;
+        Ok(io::BufReader::new(self.handle_mut()).lines().map(|line| line.map_err(EzError::from)))
+    }
+
+    ///Writes all of `buf`, retrying partial and interrupted writes, and then flushes.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::create("foo_flush.txt").unwrap();
+    ///file.write_all_flush(b"bar").unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be written to, or with
+    ///[`io::ErrorKind::WriteZero`] if the file stops accepting data.
+    pub fn write_all_flush(&mut self, mut buf: &[u8]) -> Result<(), EzError> {
+        while !buf.is_empty() {
+            match self.write(buf) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::WriteZero, "Failed to write whole buffer").into()),
+                Ok(n) => buf = &buf[n..],
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(self.flush()?)
+    }
+
+    ///Writes `line` followed by a `\n` at the current position and flushes. The handle must be
+    ///writable, see [`EzFile::to_write`] and [`EzFile::to_append`].
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::create("foo_line.txt").unwrap();
+    ///file.write_line("bar").unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be written to.
+    pub fn write_line(&mut self, line: &str) -> Result<(), EzError> {
+        let mut buf = Vec::with_capacity(line.len() + 1);
+        buf.extend_from_slice(line.as_bytes());
+        buf.push(b'\n');
+        self.write_all_flush(&buf)
+    }
+
+    ///Writes every line, each followed by a `\n`, through a buffer and flushes once at the end.
+    ///The handle must be writable, see [`EzFile::to_write`] and [`EzFile::to_append`].
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::create("foo_lines_written.txt").unwrap();
+    ///file.write_lines(["a", "b", "c"]).unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be written to.
+    pub fn write_lines<I: IntoIterator<Item = S>, S: AsRef<str>>(&mut self, lines: I) -> Result<(), EzError> {
+        let mut out = io::BufWriter::new(&mut *self);
+        for line in lines {
+            out.write_all(line.as_ref().as_bytes())?;
+            out.write_all(b"\n")?;
+        }
+        Ok(out.flush()?)
+    }
+
+    ///Consumes the file and returns an iterator over its lines paired with their 1-based line
+    ///numbers, read through a buffer from the current position. Line endings are stripped.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_numbered.txt").unwrap();
+    ///file.write_all(b"fn main() {\n}").unwrap();
+    ///file.to_read().unwrap();
+    ///
+    ///let lines: Vec<_> = file.numbered_lines().map(Result::unwrap).collect();
+    ///assert_eq!(lines, [(1, "fn main() {".to_string()), (2, "}".to_string())]);
+    ///```
+    pub fn numbered_lines(self) -> impl Iterator<Item = Result<(usize, String), EzError>> {
+        io::BufReader::new(self).lines()
+            .enumerate()
+            .map(|(i, line)| line.map(|line| (i + 1, line)).map_err(EzError::from))
+    }
+
+    ///Consumes the file and returns an iterator over its lines from last to first, reading
+    ///backwards in chunks so only the lines being yielded are kept in memory. Line endings are
+    ///stripped, and a final line without a trailing newline is still yielded. Lines that aren't
+    ///valid UTF-8 yield an [`io::ErrorKind::InvalidData`] error.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_rev.txt").unwrap();
+    ///file.write_all(b"first\nsecond\nthird\n").unwrap();
+    ///file.to_read().unwrap();
+    ///
+    ///let lines: Vec<String> = file.lines_rev().map(Result::unwrap).collect();
+    ///assert_eq!(lines, ["third", "second", "first"]);
+    ///```
+    pub fn lines_rev(self) -> impl Iterator<Item = Result<String, EzError>> {
+        RevLines { file: self, pos: None, buf: vec![], done: false }.map(|line| line.map_err(EzError::from))
+    }
+
+    ///Consumes the file and returns an iterator over its lines where no line is held in memory
+    ///past `max_line_len` bytes, protecting against huge "lines" in untrusted or binary input.
+    ///What happens to a longer line depends on `long`: it's either yielded in several pieces, or
+    ///reported as an error. Pieces are never split inside a character, so `max_line_len` is
+    ///raised to 4 bytes if smaller. Line endings are stripped.
+    ///```
+    ///use ez_fs::{EzFile, LongLines};
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_capped.txt").unwrap();
+    ///file.write_all(b"short\nway too long\n").unwrap();
+    ///file.to_read().unwrap();
+    ///
+    ///let lines: Vec<_> = file.lines_capped(8, LongLines::Split).map(Result::unwrap).collect();
+    ///assert_eq!(lines[0].text, "short");
+    ///assert_eq!(lines[1].text, "way too ");
+    ///assert!(lines[1].truncated);
+    ///assert_eq!(lines[2].text, "long");
+    ///assert!(!lines[2].truncated);
+    ///```
+    pub fn lines_capped(self, max_line_len: usize, long: LongLines) -> impl Iterator<Item = Result<CappedLine, EzError>> {
+        CappedLines {
+            reader: io::BufReader::new(self),
+            max: max_line_len.max(4),
+            long,
+            carry: vec![],
+            done: false,
+        }.map(|line| line.map_err(EzError::from))
+    }
+
+    ///Consumes the file and returns an iterator over delimiter-separated records, each split
+    ///into its fields. There is no quoting or escaping, every delimiter byte splits. Empty fields
+    ///are kept, and a final record without a trailing `record_delim` is still yielded.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_records.csv").unwrap();
+    ///file.write_all(b"name,age\nbob,,x").unwrap();
+    ///file.to_read().unwrap();
+    ///
+    ///let records: Vec<_> = file.delimited_records(b',', b'\n').map(Result::unwrap).collect();
+    ///assert_eq!(records, [
+    ///    vec![b"name".to_vec(), b"age".to_vec()],
+    ///    vec![b"bob".to_vec(), vec![], b"x".to_vec()],
+    ///]);
+    ///```
+    pub fn delimited_records(self, field_delim: u8, record_delim: u8) -> impl Iterator<Item = Result<Vec<Vec<u8>>, EzError>> {
+        io::BufReader::new(self).split(record_delim)
+            .map(move |record| Ok(record?.split(|&b| b == field_delim).map(<[u8]>::to_vec).collect()))
+    }
+
+    ///Reads a single byte, returning [`None`] at the end of the file.
+    ///This is one read call per byte, so wrap the file in a [`std::io::BufReader`] for hot loops.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_byte.txt").unwrap();
+    ///file.write_all(b"ab").unwrap();
+    ///file.to_read().unwrap();
+    ///
+    ///assert_eq!(file.read_byte().unwrap(), Some(b'a'));
+    ///assert_eq!(file.read_byte().unwrap(), Some(b'b'));
+    ///assert_eq!(file.read_byte().unwrap(), None);
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be read.
+    pub fn read_byte(&mut self) -> Result<Option<u8>, EzError> {
+        let mut buf = [0];
+        loop {
+            match self.handle_mut().read(&mut buf) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(buf[0])),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    ///Computes a 64-bit hash of the whole file, reading it in chunks so it never has to fit in
+    ///memory. The read position is restored afterwards, so the handle must be readable.
+    ///
+    ///The hash is stable within a build, which is enough to compare files against each other,
+    ///but may change between Rust releases. Store `hash_sha256` from the `sha256` feature
+    ///instead if it has to be persisted.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut a = EzFile::write_then_read("foo_hash_a.txt", b"bar").unwrap();
+    ///let mut b = EzFile::write_then_read("foo_hash_b.txt", b"bar").unwrap();
+    ///assert_eq!(a.hash().unwrap(), b.hash().unwrap());
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be read.
+    pub fn hash(&mut self) -> Result<u64, EzError> {
+        use std::hash::Hasher;
+
+        let pos = self.handle_mut().stream_position()?;
+        self.handle_mut().seek(SeekFrom::Start(0))?;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        let mut buf = [0; 8192];
+        loop {
+            match self.handle_mut().read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => hasher.write(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(e.into()),
+            }
+        }
+        self.handle_mut().seek(SeekFrom::Start(pos))?;
+        Ok(hasher.finish())
+    }
+
+    ///Computes the SHA-256 digest of the whole file. The read position is restored afterwards,
+    ///so the handle must be readable.
+    ///# Errors
+    ///This function will error if the file can't be read.
+    #[cfg(feature = "sha256")]
+    pub fn hash_sha256(&mut self) -> Result<[u8; 32], EzError> {
+        use sha2::{Digest, Sha256};
+
+        let pos = self.handle_mut().stream_position()?;
+        self.handle_mut().seek(SeekFrom::Start(0))?;
+        let mut hasher = Sha256::new();
+        io::copy(self.handle_mut(), &mut hasher)?;
+        self.handle_mut().seek(SeekFrom::Start(pos))?;
+        Ok(hasher.finalize().into())
+    }
+
+    ///Like [`EzFile::hash_sha256`], but returns the digest as a lowercase hex string, as printed
+    ///by `sha256sum`.
+    ///# Errors
+    ///This function will error if the file can't be read.
+    #[cfg(feature = "sha256")]
+    pub fn sha256_hex(&mut self) -> Result<String, EzError> {
+        Ok(hex(&self.hash_sha256()?))
+    }
+
+    ///Hashes the whole file and checks it against an expected SHA-256 digest, as when verifying
+    ///a download. The comparison is not constant-time, so don't use it to check secrets.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_verify.txt").unwrap();
+    ///file.write_all(b"bar").unwrap();
+    ///file.to_read().unwrap();
+    ///
+    ///let digest = file.hash_sha256().unwrap();
+    ///assert!(file.verify_sha256(&digest).unwrap());
+    ///assert!(!file.verify_sha256(&[0; 32]).unwrap());
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be read.
+    #[cfg(feature = "sha256")]
+    pub fn verify_sha256(&mut self, expected: &[u8; 32]) -> Result<bool, EzError> {
+        Ok(&self.hash_sha256()? == expected)
+    }
+
+    ///Closes the file and deletes it. The handle is closed first, since Windows won't remove a
+    ///file that is still open.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let file = EzFile::create("foo_removed.txt").unwrap();
+    ///file.remove().unwrap();
+    ///assert!(!std::path::Path::new("foo_removed.txt").exists());
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be removed.
+    pub fn remove(self) -> Result<(), EzError> {
+        let path = self.path.clone();
+        drop(self);
+        Ok(fs::remove_file(path)?)
+    }
+
+    ///Deconstructs and returns the path, file handle, and metadata of a file. The file is flushed
+    ///and synced according to its [`SyncPolicy`] first, just like when it's dropped, and the
+    ///returned handle is left to the caller from then on.
+    #[must_use] pub fn into_raw(mut self) -> (PathBuf, std::fs::File, std::fs::Metadata) {
+        let handle = self.release().expect(TAKEN);
+        (std::mem::take(&mut self.path), handle, self.metadata.clone())
+    }
+
+    //flushes and syncs the handle like dropping does, then takes it out, leaving `Drop` nothing
+    //to do
+    fn release(&mut self) -> Option<std::fs::File> {
+        let mut handle = self.handle.take()?;
+        //best effort, call `flush` to see the error
+        let _ = handle.flush();
+        if self.sync != SyncPolicy::Never {
+            let _ = handle.sync_all();
+        }
+        Some(handle)
+    }
+
+    ///Sets when this file syncs its writes to disk. Defaults to [`SyncPolicy::Never`].
+    ///```
+    ///use ez_fs::{EzFile, SyncPolicy};
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_sync.txt").unwrap();
+    ///file.set_sync_policy(SyncPolicy::OnDrop);
+    ///file.write_all(b"bar").unwrap();
+    /////synced here
+    ///drop(file);
+    ///```
+    pub fn set_sync_policy(&mut self, policy: SyncPolicy) {
+        self.sync = policy;
+    }
+
+    ///Returns the current [`SyncPolicy`].
+    #[must_use] pub fn sync_policy(&self) -> SyncPolicy {
+        self.sync
+    }
+
+    ///Forces all written data and metadata to disk, waiting until it's done. Unlike
+    ///[`flush`](std::io::Write::flush), this survives a crash or power loss. See
+    ///[`std::fs::File::sync_all`].
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_sync_all.txt").unwrap();
+    ///file.write_all(b"bar").unwrap();
+    ///file.sync_all().unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if the data can't be written to disk.
+    pub fn sync_all(&self) -> Result<(), EzError> {
+        Ok(self.handle().sync_all()?)
+    }
+
+    ///Like [`EzFile::sync_all`], but may skip metadata that isn't needed to read the data back,
+    ///such as the modification time. See [`std::fs::File::sync_data`].
+    ///# Errors
+    ///This function will error if the data can't be written to disk.
+    pub fn sync_data(&self) -> Result<(), EzError> {
+        Ok(self.handle().sync_data()?)
+    }
+
+
+    ///Returns the metadata cached when the file was opened or last refreshed, for anything the
+    ///delegates below don't cover, like platform `MetadataExt` traits. See
+    ///[`EzFile::refresh_metadata`].
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let file = EzFile::open("Cargo.toml").unwrap();
+    ///assert!(file.metadata().is_file());
+    ///```
+    #[must_use] pub fn metadata(&self) -> &std::fs::Metadata {
+        &self.metadata
+    }
 
     //metadata delagates
     ///Returns the last access time of the file.
     ///Derived from [`std::fs::Metadata`].
-    pub fn accessed(&self) -> io::Result<SystemTime> {
-        self.metadata.accessed()
+    pub fn accessed(&self) -> Result<SystemTime, EzError> {
+        Ok(self.metadata.accessed()?)
     }
 
     ///Returns the creation time of the file.
     ///Derived from [`std::fs::Metadata`].
-    pub fn created(&self) -> io::Result<SystemTime> {
-        self.metadata.created()
+    pub fn created(&self) -> Result<SystemTime, EzError> {
+        Ok(self.metadata.created()?)
     }
 
     ///Returns the last modification time listed in the file.
     ///Derived from [`std::fs::Metadata`].
-    pub fn modified(&self) -> io::Result<SystemTime> {
-        self.metadata.modified()
+    pub fn modified(&self) -> Result<SystemTime, EzError> {
+        Ok(self.metadata.modified()?)
+    }
+
+    ///Sets the last modification time of the file, updating the cached metadata.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::time::{Duration, SystemTime};
+    ///
+    ///let mut file = EzFile::create("foo_touch.txt").unwrap();
+    ///let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    ///file.set_modified(time).unwrap();
+    ///assert_eq!(file.modified().unwrap(), time);
+    ///```
+    ///# Errors
+    ///This function will error if the time can't be set. Some platforms, such as Windows,
+    ///need a writable handle for this.
+    pub fn set_modified(&mut self, time: SystemTime) -> Result<(), EzError> {
+        self.handle().set_modified(time)?;
+        self.refresh_metadata()
     }
 
     ///Returns the permissions of the file.
@@ -85,48 +1001,697 @@ impl EzFile {
         self.metadata.permissions()
     }
 
+    ///Returns `true` if the file is read-only, see [`std::fs::Permissions::readonly`].
+    ///Derived from [`std::fs::Metadata`].
+    #[must_use] pub fn is_readonly(&self) -> bool {
+        self.metadata.permissions().readonly()
+    }
+
+    ///Changes the permissions of the file, updating the cached metadata.
+    ///# Errors
+    ///This function will error if the permissions can't be changed.
+    pub fn set_permissions(&mut self, perms: std::fs::Permissions) -> Result<(), EzError> {
+        self.handle().set_permissions(perms)?;
+        self.refresh_metadata()
+    }
+
+    ///Makes the file read-only or writable, updating the cached metadata. On Unix, making it
+    ///writable only grants write access to the owner, rather than to everyone like
+    ///[`std::fs::Permissions::set_readonly`] does.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::create("foo_readonly.txt").unwrap();
+    ///file.set_readonly(true).unwrap();
+    ///assert!(file.permissions().readonly());
+    ///file.set_readonly(false).unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if the permissions can't be changed.
+    pub fn set_readonly(&mut self, readonly: bool) -> Result<(), EzError> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = self.permissions().mode();
+            self.set_mode(if readonly { mode & !0o222 } else { mode | 0o200 })
+        }
+        #[cfg(not(unix))]
+        {
+            let mut perms = self.permissions();
+            perms.set_readonly(readonly);
+            self.set_permissions(perms)
+        }
+    }
+
+    ///Sets the Unix permission bits of the file, like `chmod`, updating the cached metadata.
+    ///# Errors
+    ///This function will error if the permissions can't be changed.
+    #[cfg(unix)]
+    pub fn set_mode(&mut self, mode: u32) -> Result<(), EzError> {
+        use std::os::unix::fs::PermissionsExt;
+        self.set_permissions(std::fs::Permissions::from_mode(mode))
+    }
+
+    ///Shrinks or extends the file to `size` bytes, padding with zeros when extending, and
+    ///updates the cached metadata. The cursor doesn't move. The handle must be writable, see
+    ///[`EzFile::to_write`] and [`EzFile::to_rw`]; append handles work on Unix but not Windows.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::create("foo_truncate.txt").unwrap();
+    ///file.write_line("bar").unwrap();
+    ///file.truncate(0).unwrap();
+    ///assert!(file.is_empty());
+    ///```
+    ///# Errors
+    ///This function will error if the handle isn't writable or the size can't be changed.
+    pub fn truncate(&mut self, size: u64) -> Result<(), EzError> {
+        self.handle().set_len(size).map_err(|e| {
+            //a handle that isn't writable shows up as one of these, depending on the platform
+            #[cfg(target_os = "linux")]
+            let bad_handle = e.raw_os_error() == Some(libc::EBADF);
+            #[cfg(not(target_os = "linux"))]
+            let bad_handle = false;
+
+            let path = self.path.display();
+            if bad_handle || matches!(e.kind(), io::ErrorKind::PermissionDenied | io::ErrorKind::InvalidInput) {
+                io::Error::new(e.kind(), format!("Can't truncate {path}, the handle must be writable: {e}"))
+            } else {
+                io::Error::new(e.kind(), format!("Can't truncate {path}: {e}"))
+            }
+        })?;
+        self.refresh_metadata()
+    }
+
+    ///Returns the size of the file in bytes.
+    ///
+    ///This comes from metadata cached when the file was opened, so it won't see writes made
+    ///since then. Call [`EzFile::refresh_metadata`] first for the current size.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Write;
+    ///
+    ///let mut file = EzFile::create("foo_len.txt").unwrap();
+    ///file.write_all(b"bar").unwrap();
+    ///assert_eq!(file.len(), 0);
+    ///file.refresh_metadata().unwrap();
+    ///assert_eq!(file.len(), 3);
+    ///```
+    #[must_use] pub fn len(&self) -> u64 {
+        self.metadata.len()
+    }
+
+    ///Returns `true` if the file is empty, see [`EzFile::len`].
+    #[must_use] pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///Reloads the cached metadata from the handle, so [`EzFile::len`], [`EzFile::modified`]
+    ///and the other accessors reflect the file as it is now.
+    ///# Errors
+    ///This function will error if the metadata can't be read.
+    pub fn refresh_metadata(&mut self) -> Result<(), EzError> {
+        self.metadata = self.handle().metadata()?;
+        Ok(())
+    }
+
+    pub(crate) fn handle(&self) -> &std::fs::File {
+        self.handle.as_ref().expect(TAKEN)
+    }
+    fn handle_mut(&mut self) -> &mut std::fs::File {
+        self.handle.as_mut().expect(TAKEN)
+    }
+
     ///Returns the path of the file. 
-    #[must_use] pub fn path(&self) -> &str {
-        self.path.as_ref()
+    #[must_use] pub fn path(&self) -> &Path {
+        &self.path
+    }
+    ///Returns the path of the file as a `&str`, or [`None`] if it isn't valid UTF-8.
+    #[must_use] pub fn path_str(&self) -> Option<&str> {
+        self.path.to_str()
+    }
+
+    ///Returns the extension of the file, without the dot. Only the last one counts, so
+    ///`archive.tar.gz` gives `gz`, and dotfiles like `.gitignore` have none. Returns [`None`] if
+    ///there's no extension or it isn't valid UTF-8.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let file = EzFile::open("Cargo.toml").unwrap();
+    ///assert_eq!(file.extension(), Some("toml"));
+    ///```
+    #[must_use] pub fn extension(&self) -> Option<&str> {
+        self.path.extension()?.to_str()
+    }
+    ///Returns the final component of the path, or [`None`] if it isn't valid UTF-8.
+    #[must_use] pub fn file_name(&self) -> Option<&str> {
+        self.path.file_name()?.to_str()
+    }
+    ///Returns `true` if the file is hidden: its name starts with a `.`, or on Windows it has the
+    ///hidden attribute.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///assert!(EzFile::open(".gitignore").unwrap().is_hidden());
+    ///assert!(!EzFile::open("Cargo.toml").unwrap().is_hidden());
+    ///```
+    #[must_use] pub fn is_hidden(&self) -> bool {
+        self.path.file_name().is_some_and(|name| crate::dir::hidden(name, || Ok(self.metadata.clone())))
+    }
+    ///Returns the file name without its extension, so `archive.tar.gz` gives `archive.tar` and
+    ///`.gitignore` stays `.gitignore`. Returns [`None`] if it isn't valid UTF-8.
+    #[must_use] pub fn stem(&self) -> Option<&str> {
+        self.path.file_stem()?.to_str()
+    }
+    
+    ///Renames or moves the file to `new_path`, replacing anything already there, and updates
+    ///this handle to match. The open handle is kept, so the cursor and read/write mode carry
+    ///over. Moves across filesystems aren't supported and return the error from the OS.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::create("foo_before.txt").unwrap();
+    ///file.rename("foo_after.txt").unwrap();
+    ///assert_eq!(file.path(), std::path::Path::new("foo_after.txt"));
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be moved to `new_path`.
+    pub fn rename(&mut self, new_path: impl AsRef<Path>) -> Result<(), EzError> {
+        let new_path = new_path.as_ref();
+        fs::rename(&self.path, new_path)?;
+        self.path = new_path.to_owned();
+        self.refresh_metadata()
+    }
+
+    ///Copies the file to `dest`, replacing anything already there, and returns a read-only
+    ///handle to the copy. Copying a file onto itself leaves it untouched and just reopens it.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let file = EzFile::open("Cargo.toml").unwrap();
+    ///let copy = file.copy_to("foo_copy.toml").unwrap();
+    ///assert_eq!(copy.len(), file.len());
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be copied to `dest`.
+    pub fn copy_to(&self, dest: impl AsRef<Path>) -> Result<EzFile, EzError> {
+        let dest = dest.as_ref();
+        //`fs::copy` onto the same file truncates it before reading
+        let same = dest.exists() && fs::canonicalize(&self.path)? == fs::canonicalize(dest)?;
+        if !same {
+            fs::copy(&self.path, dest)?;
+        }
+        Self::open(dest)
+    }
+
+    //rewrites the stored path after the file has been moved along with a parent directory
+    pub(crate) fn rebase(&mut self, from: &Path, to: &Path) {
+        if let Ok(rest) = self.path.strip_prefix(from) {
+            self.path = to.join(rest);
+        }
     }
     
     ///Converts a read-only handle to a write-only handle. Calling this on an already WO handle
     ///will do nothing.
-    pub fn to_write(&mut self) -> io::Result<()> {
-        self.handle = std::fs::File::create(&self.path)?;
+    pub fn to_write(&mut self) -> Result<(), EzError> {
+        self.handle = Some(std::fs::File::create(&self.path)?);
+        self.append = false;
         Ok(())
     }
 
     ///Converts a write-only handle to a read-only handle. Calling this on an already RO handle
     ///will do nothing.
-    pub fn to_read(&mut self) -> io::Result<()> {
-        self.handle = std::fs::File::open(&self.path)?;
+    pub fn to_read(&mut self) -> Result<(), EzError> {
+        self.handle = Some(std::fs::File::open(&self.path)?);
+        self.append = false;
+        Ok(())
+    }
+
+    ///Converts the handle to a read-write handle, see [`EzFile::open_rw`] for how reads and
+    ///writes share the cursor. The cursor starts back at the beginning of the file.
+    pub fn to_rw(&mut self) -> Result<(), EzError> {
+        self.handle = Some(fs::OpenOptions::new().read(true).write(true).open(&self.path)?);
+        self.append = false;
         Ok(())
     }
 
+    ///Converts the handle to an append-only handle, creating the file if it was removed in the
+    ///meantime. Unlike [`EzFile::to_write`], this keeps the existing contents.
+    pub fn to_append(&mut self) -> Result<(), EzError> {
+        self.handle = Some(fs::OpenOptions::new().append(true).create(true).open(&self.path)?);
+        self.append = true;
+        self.refresh_metadata()
+    }
+
+    ///Appends `data` to the end of the file and flushes, switching the handle to append mode
+    ///with [`EzFile::to_append`] first unless it was opened by [`EzFile::append`] or already
+    ///switched. The handle stays in append mode afterwards.
+    ///```
+    ///use ez_fs::EzFile;
+    ///
+    ///let mut file = EzFile::create("foo_append_bytes.txt").unwrap();
+    ///file.append_bytes(b"one\n").unwrap();
+    ///file.append_bytes(b"two\n").unwrap();
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be reopened or written to.
+    pub fn append_bytes(&mut self, data: &[u8]) -> Result<(), EzError> {
+        if !self.append {
+            self.to_append()?;
+        }
+        self.write_all_flush(data)
+    }
+
+}
+
+//generates fixed-width integer readers/writers in both byte orders
+macro_rules! int_rw {
+    ($($t:ty => $read_le:ident, $read_be:ident, $write_le:ident, $write_be:ident;)*) => {
+        impl EzFile {$(
+            #[doc = concat!("Reads a little-endian [`", stringify!($t), "`].")]
+            ///# Errors
+            ///This function will error if the file can't be read or ends early.
+            pub fn $read_le(&mut self) -> Result<$t, EzError> {
+                let mut buf = [0; std::mem::size_of::<$t>()];
+                self.read_exact(&mut buf)?;
+                Ok(<$t>::from_le_bytes(buf))
+            }
+            #[doc = concat!("Reads a big-endian [`", stringify!($t), "`].")]
+            ///# Errors
+            ///This function will error if the file can't be read or ends early.
+            pub fn $read_be(&mut self) -> Result<$t, EzError> {
+                let mut buf = [0; std::mem::size_of::<$t>()];
+                self.read_exact(&mut buf)?;
+                Ok(<$t>::from_be_bytes(buf))
+            }
+            #[doc = concat!("Writes a little-endian [`", stringify!($t), "`].")]
+            ///# Errors
+            ///This function will error if the file can't be written to.
+            pub fn $write_le(&mut self, value: $t) -> Result<(), EzError> {
+                Ok(self.write_all(&value.to_le_bytes())?)
+            }
+            #[doc = concat!("Writes a big-endian [`", stringify!($t), "`].")]
+            ///# Errors
+            ///This function will error if the file can't be written to.
+            pub fn $write_be(&mut self, value: $t) -> Result<(), EzError> {
+                Ok(self.write_all(&value.to_be_bytes())?)
+            }
+        )*}
+    };
+}
+int_rw! {
+    u16 => read_u16_le, read_u16_be, write_u16_le, write_u16_be;
+    u32 => read_u32_le, read_u32_be, write_u32_le, write_u32_be;
+    u64 => read_u64_le, read_u64_be, write_u64_le, write_u64_be;
+    u128 => read_u128_le, read_u128_be, write_u128_le, write_u128_be;
+    i16 => read_i16_le, read_i16_be, write_i16_le, write_i16_be;
+    i32 => read_i32_le, read_i32_be, write_i32_le, write_i32_be;
+    i64 => read_i64_le, read_i64_be, write_i64_le, write_i64_be;
+    i128 => read_i128_le, read_i128_be, write_i128_le, write_i128_be;
 }
 
 impl io::Read for EzFile {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         //self.to_read()?;
-        self.handle.read(buf)
+        self.handle_mut().read(buf)
     }
 } 
 impl io::Write for EzFile {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         //self.to_write()?;
-        self.handle.write(buf) 
+        self.handle_mut().write(buf) 
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        self.handle.flush() 
+        self.handle_mut().flush()?;
+        if self.sync == SyncPolicy::Always {
+            self.handle().sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+impl io::Seek for EzFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.handle_mut().seek(pos)
+    }
+}
+
+impl Drop for EzFile {
+    fn drop(&mut self) {
+        self.release();
     }
 }
 
 impl Display for EzFile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.path())
+        write!(f, "{}", self.path.display())
+    }
+}
+
+impl PartialEq for EzFile {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+impl Eq for EzFile {}
+
+//state for `EzFile::lines_capped`, `carry` holds the start of a character cut off by the cap
+struct CappedLines {
+    reader: io::BufReader<EzFile>,
+    max: usize,
+    long: LongLines,
+    carry: Vec<u8>,
+    done: bool,
+}
+impl CappedLines {
+    fn step(&mut self) -> io::Result<Option<CappedLine>> {
+        let mut piece = std::mem::take(&mut self.carry);
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                self.done = true;
+                return if piece.is_empty() { Ok(None) } else { Self::line(piece, false).map(Some) }
+            }
+
+            let room = &available[..available.len().min(self.max - piece.len())];
+            if let Some(i) = room.iter().position(|&b| b == b'\n') {
+                piece.extend_from_slice(&room[..i]);
+                self.reader.consume(i + 1);
+                return Self::line(piece, false).map(Some)
+            }
+            let taken = room.len();
+            piece.extend_from_slice(room);
+            self.reader.consume(taken);
+
+            if piece.len() == self.max {
+                //a line ending (or the end) right at the cap means the line fit exactly
+                let ending = match self.reader.fill_buf()? {
+                    [] => Some(0),
+                    [b'\n', ..] => Some(1),
+                    [b'\r', b'\n', ..] => Some(2),
+                    _ => None,
+                };
+                if let Some(len) = ending {
+                    self.reader.consume(len);
+                    return Self::line(piece, false).map(Some)
+                }
+
+                //the buffer can end between `\r` and what follows it, so look past it
+                let mut cr = false;
+                if self.reader.buffer() == b"\r" {
+                    self.reader.consume(1);
+                    if self.reader.fill_buf()?.first() == Some(&b'\n') {
+                        self.reader.consume(1);
+                        return Self::line(piece, false).map(Some)
+                    }
+                    cr = true;
+                }
+
+                if self.long == LongLines::Error {
+                    self.done = true;
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Line is longer than {} bytes", self.max)))
+                }
+                if let Err(e) = std::str::from_utf8(&piece) {
+                    if e.error_len().is_none() {
+                        self.carry = piece.split_off(e.valid_up_to());
+                    }
+                }
+                if cr {
+                    self.carry.push(b'\r');
+                }
+                return Self::line(piece, true).map(Some)
+            }
+        }
+    }
+
+    fn line(mut bytes: Vec<u8>, truncated: bool) -> io::Result<CappedLine> {
+        if !truncated && bytes.last() == Some(&b'\r') {
+            bytes.pop();
+        }
+        let text = String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(CappedLine { text, truncated })
+    }
+}
+impl Iterator for CappedLines {
+    type Item = io::Result<CappedLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None
+        }
+        let line = self.step();
+        if line.is_err() {
+            self.done = true;
+        }
+        line.transpose()
+    }
+}
+
+//state for `EzFile::lines_rev`, `buf` holds the bytes between `pos` and the last yielded line
+struct RevLines {
+    file: EzFile,
+    pos: Option<u64>,
+    buf: Vec<u8>,
+    done: bool,
+}
+impl RevLines {
+    const CHUNK: u64 = 8 * 1024;
+
+    //starts at the end of the file, skipping a trailing newline so it doesn't yield an empty line
+    //returns `None` for an empty file, which has no lines at all
+    fn start(&mut self) -> io::Result<Option<u64>> {
+        let len = self.file.handle().metadata()?.len();
+        if len == 0 {
+            return Ok(None)
+        }
+        self.file.handle_mut().seek(SeekFrom::Start(len - 1))?;
+        let mut last = [0];
+        self.file.handle_mut().read_exact(&mut last)?;
+        Ok(Some(if last[0] == b'\n' { len - 1 } else { len }))
+    }
+
+    fn line(mut bytes: Vec<u8>) -> io::Result<String> {
+        if bytes.last() == Some(&b'\r') {
+            bytes.pop();
+        }
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn step(&mut self) -> io::Result<Option<String>> {
+        let mut pos = match self.pos {
+            Some(pos) => pos,
+            None => match self.start()? {
+                Some(pos) => pos,
+                None => {
+                    self.done = true;
+                    return Ok(None)
+                },
+            },
+        };
+        self.pos = Some(pos);
+
+        loop {
+            if let Some(i) = self.buf.iter().rposition(|&b| b == b'\n') {
+                let line = self.buf.split_off(i + 1);
+                self.buf.pop();
+                return Self::line(line).map(Some)
+            }
+            if pos == 0 {
+                self.done = true;
+                return Self::line(std::mem::take(&mut self.buf)).map(Some)
+            }
+
+            let start = pos.saturating_sub(Self::CHUNK);
+            let mut chunk = vec![0; (pos - start) as usize];
+            self.file.handle_mut().seek(SeekFrom::Start(start))?;
+            self.file.handle_mut().read_exact(&mut chunk)?;
+            chunk.append(&mut self.buf);
+            self.buf = chunk;
+            pos = start;
+            self.pos = Some(pos);
+        }
+    }
+}
+impl Iterator for RevLines {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None
+        }
+        let line = self.step();
+        if line.is_err() {
+            self.done = true;
+        }
+        line.transpose()
+    }
+}
+
+//unused hidden path in the same directory as `path`, so it can be renamed over it
+fn temp_sibling(path: &Path) -> PathBuf {
+    static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    let name = path.file_name().map_or("".into(), |n| n.to_string_lossy());
+    let tmp = format!(".{name}.{}-{}.tmp", std::process::id(), COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+    path.with_file_name(tmp)
+}
+
+//`path` with `.{n}` tacked onto the end, for rotated logs
+fn numbered(path: &Path, n: usize) -> PathBuf {
+    let mut numbered = path.as_os_str().to_owned();
+    numbered.push(format!(".{n}"));
+    numbered.into()
+}
+
+//lowercase hex encoding for digests
+#[cfg(feature = "sha256")]
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+///Exclusive lock over a file created by [`EzFile::create_locked`].
+///Dereferences to the underlying [`EzFile`]. When dropped, the file is removed and unlocked.
+#[derive(Debug)]
+pub struct EzFileGuard {
+    file: EzFile,
+}
+impl Deref for EzFileGuard {
+    type Target = EzFile;
+
+    fn deref(&self) -> &Self::Target {
+        &self.file
     }
 }
+impl DerefMut for EzFileGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.file
+    }
+}
+impl io::Write for EzFileGuard {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
 
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+impl Drop for EzFileGuard {
+    fn drop(&mut self) {
+        //remove before unlocking so nobody can grab the lock on a file that's about to vanish
+        let _ = fs::remove_file(&self.file.path);
+        let _ = self.file.handle().unlock();
+    }
+}
 
+///Size-capped log file created by [`EzFile::rotating`].
+///Writing through [`io::Write`] rotates the file as needed.
+#[derive(Debug)]
+pub struct RotatingEzFile {
+    file: EzFile,
+    written: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+impl RotatingEzFile {
+    ///Returns the currently open file.
+    #[must_use] pub fn file(&self) -> &EzFile {
+        &self.file
+    }
+
+    ///Rotates the file right away, regardless of its size.
+    ///# Errors
+    ///This function will error if any of the files can't be renamed, removed or reopened.
+    pub fn rotate(&mut self) -> Result<(), EzError> {
+        self.file.flush()?;
+        let path = self.file.path.clone();
+
+        if self.max_files == 0 {
+            self.file.handle().set_len(0)?;
+        } else {
+            let _ = fs::remove_file(numbered(&path, self.max_files));
+            for i in (1..self.max_files).rev() {
+                let from = numbered(&path, i);
+                if from.exists() {
+                    fs::rename(from, numbered(&path, i + 1))?;
+                }
+            }
+            fs::rename(&path, numbered(&path, 1))?;
+            self.file = EzFile::from_handle(&path, fs::OpenOptions::new().append(true).create(true).open(&path)?)?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
+impl io::Write for RotatingEzFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+///Staged write created by [`EzFile::create_atomic`].
+///Nothing is visible at the target path until [`AtomicEzFile::commit`] is called.
+#[derive(Debug)]
+pub struct AtomicEzFile {
+    file: EzFile,
+    target: PathBuf,
+    done: bool,
+}
+impl AtomicEzFile {
+    ///Returns the path that will be replaced on commit.
+    #[must_use] pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    ///Flushes and syncs the staged data, then renames it over the target path. If the target
+    ///already exists its permissions are carried over to the new file. Returns a read-only
+    ///handle to the target.
+    ///# Errors
+    ///This function will error if the data can't be synced, the permissions can't be copied, or
+    ///the rename fails, in which case the temporary file is removed and the target is left
+    ///untouched.
+    pub fn commit(mut self) -> Result<EzFile, EzError> {
+        self.file.flush()?;
+        if let Ok(metadata) = fs::metadata(&self.target) {
+            fs::set_permissions(&self.file.path, metadata.permissions())?;
+        }
+        self.file.handle().sync_all()?;
+        fs::rename(&self.file.path, &self.target)?;
+        self.done = true;
+        EzFile::open(&self.target)
+    }
+
+    ///Throws away the staged data, leaving the target path untouched.
+    ///# Errors
+    ///This function will error if the temporary file can't be removed.
+    pub fn abort(mut self) -> Result<(), EzError> {
+        self.done = true;
+        Ok(fs::remove_file(&self.file.path)?)
+    }
+}
+impl io::Write for AtomicEzFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+impl Drop for AtomicEzFile {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = fs::remove_file(&self.file.path);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//small glob matcher, supports `*`, `?` and `**`
+//patterns and paths are split on `/`, so `*` never crosses a directory boundary
+
+///Returns `true` if `path` matches `pattern`. Both are expected to be `/` separated.
+pub(crate) fn matches(pattern: &str, path: &str) -> bool {
+    let split = |s: &str| -> Vec<Vec<char>> {
+        s.split('/').filter(|s| !s.is_empty() && *s != ".").map(|s| s.chars().collect()).collect()
+    };
+    match_components(&split(pattern), &split(path))
+}
+
+//both matchers below are the usual wildcard loop: on a mismatch, retry from the last star with
+//it swallowing one more item. only the last star ever needs retrying, so this stays linear-ish
+//instead of backtracking into every earlier star
+
+fn match_components(pattern: &[Vec<char>], path: &[Vec<char>]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < path.len() {
+        match pattern.get(p) {
+            Some(seg) if is_globstar(seg) => {
+                star = Some((p, t));
+                p += 1;
+            },
+            Some(seg) if match_segment(seg, &path[t]) => {
+                p += 1;
+                t += 1;
+            },
+            _ => match star {
+                Some((sp, st)) => {
+                    star = Some((sp, st + 1));
+                    p = sp + 1;
+                    t = st + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|seg| is_globstar(seg))
+}
+
+fn match_segment(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            },
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            },
+            _ => match star {
+                Some((sp, st)) => {
+                    star = Some((sp, st + 1));
+                    p = sp + 1;
+                    t = st + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn is_globstar(seg: &[char]) -> bool {
+    seg == ['*', '*']
+}
@@ -0,0 +1,210 @@
+use crate::{dir::{EzDir, EzEntry}, file::EzFile, error::EzError};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, ser::SerializeStruct};
+use std::{io, fs, path::{Path, PathBuf}, time::SystemTime, collections::HashMap};
+
+//what an indexed path was when it was indexed, files with their size and modification time
+#[derive(Debug, PartialEq)]
+pub(crate) enum Stamp {
+    File(u64, Option<SystemTime>),
+    Dir,
+    Symlink,
+}
+
+impl Stamp {
+    //stamps every entry below `dir` from the metadata read while walking, not including `dir`
+    fn collect(dir: &EzDir, stamps: &mut HashMap<PathBuf, Stamp>) {
+        for entry in dir.iter() {
+            match entry {
+                EzEntry::File(file) => stamps.insert(file.path().to_owned(), Self::File(file.len(), file.modified().ok())),
+                EzEntry::Dir(dir) => {
+                    Self::collect(dir, stamps);
+                    stamps.insert(dir.path().to_owned(), Self::Dir)
+                },
+                EzEntry::Symlink(path) => stamps.insert(path.clone(), Self::Symlink),
+            };
+        }
+    }
+}
+
+//on-disk form of a walked tree, without any open handles
+#[derive(Serialize, Deserialize)]
+enum IndexEntry {
+    File { path: PathBuf, size: u64, modified: Option<SystemTime> },
+    Dir { path: PathBuf, entries: Option<Vec<IndexEntry>> },
+    Symlink { path: PathBuf },
+}
+
+impl IndexEntry {
+    fn from_dir(dir: &EzDir) -> Self {
+        Self::Dir {
+            path: dir.path().to_owned(),
+            entries: dir.is_cached().then(|| dir.iter().map(|entry| match entry {
+                EzEntry::File(file) => Self::File {
+                    path: file.path().to_owned(),
+                    size: file.len(),
+                    modified: file.modified().ok(),
+                },
+                EzEntry::Dir(dir) => Self::from_dir(dir),
+                EzEntry::Symlink(path) => Self::Symlink { path: path.clone() },
+            }).collect()),
+        }
+    }
+
+    //records the indexed stamp of every entry, without touching the disk
+    fn collect_stamps(self, stamps: &mut HashMap<PathBuf, Stamp>) {
+        let (path, stamp) = match self {
+            Self::File { path, size, modified } => (path, Stamp::File(size, modified)),
+            Self::Dir { path, entries } => {
+                entries.into_iter().flatten().for_each(|e| e.collect_stamps(stamps));
+                (path, Stamp::Dir)
+            },
+            Self::Symlink { path } => (path, Stamp::Symlink),
+        };
+        stamps.insert(path, stamp);
+    }
+}
+
+impl EzDir {
+    ///Saves the cached structure of this directory to an index file at `path`: every path, and
+    ///the size and modification time of every file. Load it again in a later run with
+    ///[`EzDir::load_index`] to find out what changed in a large tree since, without keeping it
+    ///open in between.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.walk(0);
+    ///dir.save_index("foo_index.json").unwrap();
+    ///
+    ///let mut loaded = EzDir::load_index("foo_index.json").unwrap();
+    ///assert!(!loaded.indexed_files().is_empty());
+    ///assert!(loaded.revalidate().unwrap().is_empty());
+    ///```
+    ///# Errors
+    ///This function will error if `path` can't be written to.
+    pub fn save_index(&self, path: impl AsRef<Path>) -> Result<(), EzError> {
+        let out = io::BufWriter::new(fs::File::create(path)?);
+        serde_json::to_writer(out, &IndexEntry::from_dir(self)).map_err(io::Error::from)?;
+        Ok(())
+    }
+
+    ///Loads an index written by [`EzDir::save_index`] without rescanning the directory or
+    ///opening anything in it. The returned directory is uncached and only carries the indexed
+    ///paths, with the sizes and modification times of files, see [`EzDir::indexed_files`].
+    ///[`EzDir::revalidate`] then walks it and tells what changed since the index was saved.
+    ///# Errors
+    ///This function will error if `path` can't be read or isn't a valid index.
+    pub fn load_index(path: impl AsRef<Path>) -> Result<EzDir, EzError> {
+        let index: IndexEntry = serde_json::from_reader(io::BufReader::new(fs::File::open(path)?)).map_err(io::Error::from)?;
+        let IndexEntry::Dir { path, entries } = index else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Index root is not a directory").into())
+        };
+        let mut stamps = HashMap::new();
+        entries.into_iter().flatten().for_each(|e| e.collect_stamps(&mut stamps));
+        let mut dir = EzDir::from_parts(path, None);
+        dir.index = Some(stamps);
+        Ok(dir)
+    }
+
+    ///Returns the paths of every file in the index this directory was loaded from with
+    ///[`EzDir::load_index`], sorted, or nothing if it wasn't loaded from one. They are as they
+    ///were when the index was saved and may no longer exist.
+    #[must_use] pub fn indexed_files(&self) -> Vec<&Path> {
+        let mut paths: Vec<&Path> = self.index.iter()
+            .flat_map(|index| index.iter().filter(|(_, stamp)| matches!(stamp, Stamp::File(..))))
+            .map(|(path, _)| path.as_path())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    ///Walks the whole directory again and returns the paths of every file, directory or symlink
+    ///that was added or removed, and every file that was resized or modified, since the index was
+    ///loaded, or since this directory was last walked if it didn't come from an index. An entry
+    ///replaced by one of another kind counts as changed, but a retargeted symlink doesn't.
+    ///Afterwards the directory reflects the disk.
+    ///# Errors
+    ///This function will error if a directory can't be read.
+    pub fn revalidate(&mut self) -> Result<Vec<PathBuf>, EzError> {
+        let stamps = |dir: &EzDir| {
+            let mut stamps = HashMap::new();
+            Stamp::collect(dir, &mut stamps);
+            stamps
+        };
+
+        let old = match self.index.take() {
+            Some(index) => index,
+            None => stamps(self),
+        };
+        self.try_walk(0)?;
+        let new = stamps(self);
+
+        let mut changed: Vec<PathBuf> = new.iter()
+            .filter(|(path, stamp)| old.get(*path) != Some(stamp))
+            .chain(old.iter().filter(|(path, _)| !new.contains_key(*path)))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.sort();
+        Ok(changed)
+    }
+}
+
+//shape of a serialized `EzDir`, matching the `Serialize` impls below
+#[derive(Deserialize)]
+#[serde(rename = "EzDir")]
+struct DirShape {
+    path: PathBuf,
+    entries: Option<Vec<EntryShape>>,
+}
+#[derive(Deserialize)]
+#[serde(rename = "EzEntry")]
+enum EntryShape {
+    File(PathBuf),
+    Dir(DirShape),
+    Symlink(PathBuf),
+}
+
+impl DirShape {
+    fn into_dir<E: serde::de::Error>(self) -> Result<EzDir, E> {
+        let entries = self.entries.map(|v| v.into_iter().map(|entry| match entry {
+            EntryShape::File(path) => EzFile::open(&path)
+                .map(EzEntry::File)
+                .map_err(|e| E::custom(format!("Can't open {}: {e}", path.display()))),
+            EntryShape::Dir(dir) => dir.into_dir().map(EzEntry::Dir),
+            EntryShape::Symlink(path) => Ok(EzEntry::Symlink(path)),
+        }).collect()).transpose()?;
+        Ok(EzDir::from_parts(self.path, entries))
+    }
+}
+
+///Serializes the cached structure as nested paths, with `entries` left `null` for directories
+///that haven't been cached. Handles and metadata aren't included. Requires the `serde` feature.
+impl Serialize for EzDir {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut dir = serializer.serialize_struct("EzDir", 2)?;
+        dir.serialize_field("path", self.path())?;
+        dir.serialize_field("entries", &self.entries())?;
+        dir.end()
+    }
+}
+
+///Serializes a file or symlink as its path, and a directory like [`EzDir`]. Requires the
+///`serde` feature.
+impl Serialize for EzEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::File(file) => serializer.serialize_newtype_variant("EzEntry", 0, "File", file.path()),
+            Self::Dir(dir) => serializer.serialize_newtype_variant("EzEntry", 1, "Dir", dir),
+            Self::Symlink(path) => serializer.serialize_newtype_variant("EzEntry", 2, "Symlink", path),
+        }
+    }
+}
+
+///Rebuilds a tree serialized from an [`EzDir`] without rescanning it. Files are reopened, and
+///deserializing fails if any of them can't be, rather than quietly rebuilding a different tree.
+///Directories that weren't cached stay uncached. Requires the `serde` feature.
+impl<'de> Deserialize<'de> for EzDir {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        DirShape::deserialize(deserializer)?.into_dir()
+    }
+}
@@ -20,6 +20,8 @@
 //!
 //!### Reading/Writing to Files
 //!```rust
+//!# use ez_fs::*;
+//!# use std::io::{Read, Write};
 //!//open file in write-only mode
 //!let mut file = EzFile::create("foo.txt").unwrap();
 //!file.write_all(b"bar").unwrap();
@@ -34,6 +36,7 @@
 //!
 //!### Collecting Directories
 //!```rust
+//!# use ez_fs::*;
 //!//open an existing directory
 //!let dir = EzDir::new(".", true).unwrap();
 //!
@@ -45,9 +48,27 @@
 //!```
 
 
+mod error;
 mod file;
 mod dir;
-pub use crate::{dir::*, file::*};
+mod glob;
+mod ops;
+mod cached;
+#[cfg(feature = "sha256")]
+mod manifest;
+#[cfg(feature = "archive")]
+mod archive;
+#[cfg(feature = "serde")]
+mod index;
+#[cfg(feature = "mmap")]
+mod mmap;
+pub use crate::{error::*, dir::*, file::*, ops::*, cached::*};
+#[cfg(feature = "sha256")]
+pub use crate::manifest::*;
+#[cfg(feature = "archive")]
+pub use crate::archive::*;
+#[cfg(feature = "mmap")]
+pub use crate::mmap::*;
 
 #[cfg(test)]
 mod tests {
@@ -55,6 +76,14 @@ mod tests {
 
     use super::*; 
 
+    //fresh scratch directory under the system temp dir
+    fn scratch(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("ez_fs_{name}"));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
     #[test]
     fn rw_test() {
         //open file in write-only mode
@@ -113,4 +142,1823 @@ mod tests {
             println!("{file}")
         }
     }
+
+    #[test]
+    fn locked_test() {
+        let path = format!("{}/test.pid", scratch("locked"));
+        let lock = EzFile::create_locked(&path).unwrap();
+
+        let err = EzFile::create_locked(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+        assert!(std::path::Path::new(&path).exists());
+
+        drop(lock);
+        assert!(!std::path::Path::new(&path).exists());
+
+        //a stale pidfile from a crashed process is taken over
+        std::fs::write(&path, b"12345").unwrap();
+        let lock = EzFile::create_locked(&path).unwrap();
+        assert_eq!(lock.metadata().len(), 0);
+    }
+
+    #[test]
+    fn walk_globs_test() {
+        let root = scratch("walk_globs");
+        for path in ["src", "target"] {
+            std::fs::create_dir(format!("{root}/{path}")).unwrap();
+        }
+        for path in ["a.rs", "b.txt", "src/c.rs", "src/d.txt", "target/e.rs"] {
+            std::fs::write(format!("{root}/{path}"), b"").unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk_globs(0, &["**/*.rs"], &["target"]).unwrap();
+        let mut files: Vec<String> = dir.flatten().iter()
+            .map(|f| f.path_str().unwrap().trim_start_matches(&root).to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["/a.rs", "/src/c.rs"]);
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk_globs(0, &[], &["**/*.txt"]).unwrap();
+        assert_eq!(dir.flatten().len(), 3);
+    }
+
+    #[test]
+    fn swap_test() {
+        let root = scratch("swap");
+        std::fs::create_dir_all(format!("{root}/current/sub")).unwrap();
+        std::fs::create_dir(format!("{root}/new")).unwrap();
+        std::fs::write(format!("{root}/current/sub/old.txt"), b"v1").unwrap();
+        std::fs::write(format!("{root}/new/new.txt"), b"v2").unwrap();
+
+        let mut current = EzDir::new(format!("{root}/current"), false).unwrap();
+        let mut new = EzDir::new(format!("{root}/new"), false).unwrap();
+        current.walk(0);
+        new.walk(0);
+        current.swap_with(&mut new).unwrap();
+
+        assert_eq!(std::fs::read(format!("{root}/current/new.txt")).unwrap(), b"v2");
+        assert_eq!(std::fs::read(format!("{root}/new/sub/old.txt")).unwrap(), b"v1");
+
+        let paths: Vec<String> = current.flatten().iter().map(|f| f.path_str().unwrap().to_string()).collect();
+        assert_eq!(paths, [format!("{root}/current/new.txt")]);
+        let paths: Vec<String> = new.flatten().iter().map(|f| f.path_str().unwrap().to_string()).collect();
+        assert_eq!(paths, [format!("{root}/new/sub/old.txt")]);
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn manifest_test() {
+        let root = scratch("manifest");
+        let manifest = format!("{}.txt", root);
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        std::fs::write(format!("{root}/a.txt"), b"a").unwrap();
+        std::fs::write(format!("{root}/sub/b.txt"), b"b").unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.write_manifest(&manifest).unwrap();
+        assert!(dir.verify_manifest(&manifest).unwrap().is_empty());
+
+        std::fs::write(format!("{root}/a.txt"), b"c").unwrap();
+        std::fs::write(format!("{root}/sub/b.txt"), b"bb").unwrap();
+        std::fs::write(format!("{root}/new.txt"), b"").unwrap();
+        let mismatches = dir.verify_manifest(&manifest).unwrap();
+        assert_eq!(mismatches, [
+            ManifestMismatch::Hash("a.txt".to_string()),
+            ManifestMismatch::Size { path: "sub/b.txt".to_string(), expected: 1, actual: 2 },
+            ManifestMismatch::Unlisted("new.txt".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn modified_range_test() {
+        let root = scratch("modified_range");
+        let mut dir = EzDir::new(&root, false).unwrap();
+        assert_eq!(dir.newest_modified().unwrap(), None);
+
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        let old = std::fs::File::create(format!("{root}/old.txt")).unwrap();
+        let new = std::fs::File::create(format!("{root}/sub/new.txt")).unwrap();
+        let epoch = std::time::SystemTime::UNIX_EPOCH;
+        old.set_modified(epoch + std::time::Duration::from_secs(100)).unwrap();
+        new.set_modified(epoch + std::time::Duration::from_secs(200)).unwrap();
+
+        assert_eq!(dir.newest_modified().unwrap(), Some(epoch + std::time::Duration::from_secs(200)));
+        assert_eq!(dir.oldest_modified().unwrap(), Some(epoch + std::time::Duration::from_secs(100)));
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn archive_test() {
+        let root = scratch("archive");
+        let path = format!("{root}/project.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("a.txt", options).unwrap();
+        zip.write_all(b"a").unwrap();
+        zip.start_file("sub/b.txt", options).unwrap();
+        zip.write_all(b"b").unwrap();
+        zip.finish().unwrap();
+
+        let mut dir = EzDir::open_archive(&path).unwrap();
+        let extracted = dir.path().to_owned();
+        assert_eq!(dir.source(), std::path::Path::new(&path));
+        let mut files: Vec<_> = dir.iter_files_mut().collect();
+        files.sort_by(|a, b| a.path().cmp(b.path()));
+        assert_eq!(files.len(), 2);
+        assert!(files[0].permissions().readonly());
+
+        let mut buf = String::new();
+        files[1].read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "b");
+
+        //the extracted copy goes away with the tree
+        drop(dir);
+        assert!(!extracted.exists());
+
+        assert!(EzDir::open_archive_limited(&path, 1).is_err());
+        assert!(EzDir::open_archive_limited(&path, 2).is_ok());
+
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        zip.start_file("../escaped.txt", options).unwrap();
+        zip.finish().unwrap();
+        assert!(EzDir::open_archive(&path).is_err());
+    }
+
+    #[test]
+    fn deepest_test() {
+        let root = scratch("deepest");
+        let mut dir = EzDir::new(&root, false).unwrap();
+        assert_eq!(dir.deepest_path().unwrap(), None);
+
+        std::fs::create_dir_all(format!("{root}/a/b")).unwrap();
+        std::fs::create_dir_all(format!("{root}/c/d")).unwrap();
+        std::fs::write(format!("{root}/a/b/file.txt"), b"").unwrap();
+
+        let (depth, path) = dir.deepest_path().unwrap().unwrap();
+        assert_eq!(path, std::path::Path::new(&format!("{root}/a/b/file.txt")));
+        assert_eq!(depth, std::path::Path::new(&root).components().count() + 3);
+    }
+
+    #[test]
+    fn rotating_test() {
+        let path = format!("{}/log.txt", scratch("rotating"));
+        let mut log = EzFile::rotating(&path, 4, 2).unwrap();
+        for line in ["aaa", "bbb", "ccc", "ddd"] {
+            log.write_all(line.as_bytes()).unwrap();
+        }
+        drop(log);
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "ddd");
+        assert_eq!(std::fs::read_to_string(format!("{path}.1")).unwrap(), "ccc");
+        assert_eq!(std::fs::read_to_string(format!("{path}.2")).unwrap(), "bbb");
+        assert!(!std::path::Path::new(&format!("{path}.3")).exists());
+    }
+
+    #[test]
+    fn total_len_test() {
+        let root = scratch("total_len");
+        std::fs::create_dir_all(format!("{root}/a/b")).unwrap();
+        std::fs::write(format!("{root}/one.txt"), b"").unwrap();
+        std::fs::write(format!("{root}/a/b/two.txt"), b"").unwrap();
+
+        let mut dir = EzDir::new(&root, true).unwrap();
+        assert_eq!(dir.total_len(), None);
+        dir.walk(0);
+        assert_eq!(dir.total_len(), Some(4));
+        dir.cache().unwrap();
+        assert_eq!(dir.total_len(), None);
+
+        //touching only files keeps the count, anything that can reshape the tree forgets it
+        dir.walk(0);
+        dir.iter_files_mut().for_each(|_| {});
+        assert_eq!(dir.total_len(), Some(4));
+        for entry in dir.iter_mut() {
+            if let Some(sub) = entry.as_dir_mut() {
+                sub.cache().unwrap();
+            }
+        }
+        assert_eq!(dir.total_len(), None);
+    }
+
+    #[test]
+    fn walk_chunked_test() {
+        let root = scratch("walk_chunked");
+        for sub in ["a/b", "c/d", "e"] {
+            std::fs::create_dir_all(format!("{root}/{sub}")).unwrap();
+            std::fs::write(format!("{root}/{sub}/file.txt"), b"").unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        let steps = dir.walk_chunked(0, 2).collect::<Result<Vec<_>, EzError>>().unwrap();
+        //6 directories in batches of 2
+        assert_eq!(steps.len(), 3);
+        assert_eq!(dir.total_len(), Some(8));
+
+        let mut walked = EzDir::new(&root, false).unwrap();
+        walked.walk(0);
+        assert_eq!(dir.flatten().len(), walked.flatten().len());
+    }
+
+    #[test]
+    fn int_rw_test() {
+        let path = format!("{}/ints.bin", scratch("int_rw"));
+        let mut file = EzFile::create(&path).unwrap();
+        file.write_u32_le(0xDEAD_BEEF).unwrap();
+        file.write_u16_be(0x0102).unwrap();
+        file.write_i64_le(-2).unwrap();
+        file.to_read().unwrap();
+
+        let mut buf = [0; 6];
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0xEF, 0xBE, 0xAD, 0xDE, 0x01, 0x02]);
+        assert_eq!(file.read_i64_le().unwrap(), -2);
+        assert!(file.read_u16_le().is_err());
+
+        file.to_read().unwrap();
+        assert_eq!(file.read_u32_be().unwrap(), 0xEFBE_ADDE);
+    }
+
+    #[test]
+    fn changed_since_test() {
+        let root = scratch("changed_since");
+        let epoch = std::time::SystemTime::UNIX_EPOCH;
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        for (name, secs) in [("old.txt", 100), ("sub/new.txt", 300)] {
+            let file = std::fs::File::create(format!("{root}/{name}")).unwrap();
+            file.set_modified(epoch + std::time::Duration::from_secs(secs)).unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        let changed = dir.changed_since(epoch + std::time::Duration::from_secs(200)).unwrap();
+        assert_eq!(changed, [std::path::PathBuf::from(format!("{root}/sub/new.txt"))]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn index_test() {
+        let root = scratch("index");
+        let index = format!("{root}.json");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        std::fs::create_dir(format!("{root}/empty")).unwrap();
+        for name in ["same.txt", "gone.txt", "sub/grown.txt"] {
+            std::fs::write(format!("{root}/{name}"), b"a").unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(0);
+        dir.save_index(&index).unwrap();
+        drop(dir);
+
+        std::fs::remove_file(format!("{root}/gone.txt")).unwrap();
+        std::fs::write(format!("{root}/sub/grown.txt"), b"abc").unwrap();
+        std::fs::write(format!("{root}/new.txt"), b"").unwrap();
+        //directories and symlinks without any files are noticed too
+        std::fs::remove_dir(format!("{root}/empty")).unwrap();
+        std::fs::create_dir(format!("{root}/added")).unwrap();
+
+        //nothing is opened or checked on load
+        let mut dir = EzDir::load_index(&index).unwrap();
+        assert!(!dir.is_cached());
+        let indexed: Vec<std::path::PathBuf> = ["gone.txt", "same.txt", "sub/grown.txt"].iter()
+            .map(|name| format!("{root}/{name}").into())
+            .collect();
+        assert_eq!(dir.indexed_files(), indexed);
+        let changed = dir.revalidate().unwrap();
+        let expected: Vec<std::path::PathBuf> = ["added", "empty", "gone.txt", "new.txt", "sub/grown.txt"].iter()
+            .map(|name| format!("{root}/{name}").into())
+            .collect();
+        assert_eq!(changed, expected);
+        assert!(dir.revalidate().unwrap().is_empty());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("same.txt", format!("{root}/link")).unwrap();
+            assert_eq!(dir.revalidate().unwrap(), [std::path::PathBuf::from(format!("{root}/link"))]);
+        }
+    }
+
+    #[test]
+    fn atomic_test() {
+        let root = scratch("atomic");
+        let path = format!("{root}/config.txt");
+        std::fs::write(&path, b"old").unwrap();
+
+        let mut staged = EzFile::create_atomic(&path).unwrap();
+        staged.write_all(b"new").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"old");
+        drop(staged);
+        assert_eq!(std::fs::read(&path).unwrap(), b"old");
+
+        let mut staged = EzFile::create_atomic(&path).unwrap();
+        staged.write_all(b"new").unwrap();
+        staged.commit().unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+
+        //the replaced file keeps the target's permissions
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+            let staged = EzFile::create_atomic(&path).unwrap();
+            staged.commit().unwrap();
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        //only the target is left behind
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn natural_sort_test() {
+        let root = scratch("natural_sort");
+        for name in ["file10", "file2", "file1", "file02b", "a"] {
+            std::fs::write(format!("{root}/{name}"), b"").unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, true).unwrap();
+        dir.sort_natural();
+        let names: Vec<String> = dir.iter()
+            .map(|e| e.to_string().trim_start_matches(&format!("{root}/")).to_string())
+            .collect();
+        assert_eq!(names, ["a", "file1", "file2", "file02b", "file10"]);
+    }
+
+    #[test]
+    fn walk_parallel_test() {
+        let root = scratch("walk_parallel");
+        for i in 0..8 {
+            std::fs::create_dir_all(format!("{root}/{i}/inner/deep")).unwrap();
+            std::fs::write(format!("{root}/{i}/inner/deep/file.txt"), b"").unwrap();
+        }
+
+        let mut serial = EzDir::new(&root, false).unwrap();
+        serial.walk(2);
+        let mut parallel = EzDir::new(&root, false).unwrap();
+        parallel.walk_parallel_with_threads(2, 3).unwrap();
+        assert_eq!(parallel.total_len(), serial.total_len());
+        assert_eq!(parallel.iter_files().count(), 0);
+
+        parallel.walk_parallel_with_threads(0, 3).unwrap();
+        assert_eq!(parallel.iter_files().count(), 8);
+    }
+
+    #[test]
+    fn lines_rev_test() {
+        let root = scratch("lines_rev");
+        let rev = |contents: &[u8]| -> Vec<String> {
+            std::fs::write(format!("{root}/log.txt"), contents).unwrap();
+            EzFile::open(format!("{root}/log.txt")).unwrap().lines_rev().map(Result::unwrap).collect()
+        };
+
+        assert!(rev(b"").is_empty());
+        assert_eq!(rev(b"a\r\nb"), ["b", "a"]);
+        assert_eq!(rev(b"\na\n\n"), ["", "a", ""]);
+        assert_eq!(rev("h\u{e9}llo\n\u{1f980}\n".as_bytes()), ["\u{1f980}", "h\u{e9}llo"]);
+
+        //longer than a chunk, so lines straddle reads
+        let long: Vec<String> = (0..5000).map(|i| format!("line {i} \u{e9}")).collect();
+        let mut expected = long.clone();
+        expected.reverse();
+        assert_eq!(rev(long.join("\n").as_bytes()), expected);
+    }
+
+    #[test]
+    fn lines_capped_test() {
+        let path = format!("{}/input.txt", scratch("lines_capped"));
+        std::fs::write(&path, "1234\n12345678\n\u{e9}\u{e9}\u{e9}\r\n".as_bytes()).unwrap();
+
+        let lines: Vec<(String, bool)> = EzFile::open(&path).unwrap()
+            .lines_capped(4, LongLines::Split)
+            .map(|l| l.map(|l| (l.text, l.truncated)).unwrap())
+            .collect();
+        assert_eq!(lines, [
+            ("1234".to_string(), false),
+            ("1234".to_string(), true),
+            ("5678".to_string(), false),
+            //6 bytes, split between characters
+            ("\u{e9}\u{e9}".to_string(), true),
+            ("\u{e9}".to_string(), false),
+        ]);
+
+        let mut lines = EzFile::open(&path).unwrap().lines_capped(4, LongLines::Error);
+        assert_eq!(lines.next().unwrap().unwrap().text, "1234");
+        assert_eq!(lines.next().unwrap().unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        assert!(lines.next().is_none());
+
+        //a CRLF right after the cap ends the line instead of overflowing it
+        std::fs::write(&path, b"abcde\r\nfg\r\n").unwrap();
+        let lines: Vec<(String, bool)> = EzFile::open(&path).unwrap()
+            .lines_capped(5, LongLines::Split)
+            .map(|l| l.map(|l| (l.text, l.truncated)).unwrap())
+            .collect();
+        assert_eq!(lines, [("abcde".to_string(), false), ("fg".to_string(), false)]);
+        let lines: Vec<String> = EzFile::open(&path).unwrap()
+            .lines_capped(5, LongLines::Error)
+            .map(|l| l.unwrap().text)
+            .collect();
+        assert_eq!(lines, ["abcde", "fg"]);
+
+        //same, with the buffer ending between the `\r` and the `\n`
+        let long = "a".repeat(8191);
+        std::fs::write(&path, format!("{long}\r\nfg")).unwrap();
+        let lines: Vec<String> = EzFile::open(&path).unwrap()
+            .lines_capped(8191, LongLines::Error)
+            .map(|l| l.unwrap().text)
+            .collect();
+        assert_eq!(lines, [long.as_str(), "fg"]);
+    }
+
+    #[test]
+    fn touch_all_test() {
+        let root = scratch("touch_all");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        std::fs::write(format!("{root}/a.txt"), b"").unwrap();
+        std::fs::write(format!("{root}/sub/b.txt"), b"").unwrap();
+
+        let time = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1234);
+        let mut dir = EzDir::new(&root, false).unwrap();
+        assert_eq!(dir.touch_all(time).unwrap(), 2);
+        assert_eq!(dir.newest_modified().unwrap(), Some(time));
+        assert_eq!(dir.oldest_modified().unwrap(), Some(time));
+    }
+
+    #[test]
+    fn size_breakdown_test() {
+        let root = scratch("size_breakdown");
+        std::fs::create_dir_all(format!("{root}/big/inner")).unwrap();
+        std::fs::create_dir(format!("{root}/small")).unwrap();
+        std::fs::write(format!("{root}/loose.txt"), [0; 5]).unwrap();
+        std::fs::write(format!("{root}/big/a.txt"), [0; 10]).unwrap();
+        std::fs::write(format!("{root}/big/inner/b.txt"), [0; 10]).unwrap();
+        std::fs::write(format!("{root}/small/c.txt"), [0; 1]).unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        let sizes = dir.size_breakdown().unwrap();
+        assert_eq!(sizes, [
+            (format!("{root}/big").into(), 20),
+            (root.clone().into(), 5),
+            (format!("{root}/small").into(), 1),
+        ]);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn shared_reader_test() {
+        use std::io::{Seek, SeekFrom};
+
+        let path = format!("{}/shared.txt", scratch("shared_reader"));
+        std::fs::write(&path, b"hello world").unwrap();
+        let file = EzFile::open(&path).unwrap();
+
+        let mut a = file.shared_reader().unwrap();
+        let mut b = a.clone();
+        let mut buf = [0; 5];
+        a.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+
+        b.seek(SeekFrom::End(-5)).unwrap();
+        b.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"world");
+
+        let mut rest = String::new();
+        a.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, " world");
+        assert!(b.seek(SeekFrom::Current(-20)).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn broken_symlinks_test() {
+        let root = scratch("broken_symlinks");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        std::fs::write(format!("{root}/real.txt"), b"").unwrap();
+        std::os::unix::fs::symlink("real.txt", format!("{root}/good")).unwrap();
+        std::os::unix::fs::symlink("../missing.txt", format!("{root}/sub/bad")).unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        assert_eq!(dir.broken_symlinks().unwrap(), [
+            (format!("{root}/sub/bad").into(), "../missing.txt".into()),
+        ]);
+    }
+
+    #[test]
+    fn ops_test() {
+        let root = scratch("ops");
+        std::fs::create_dir_all(format!("{root}/dir/sub")).unwrap();
+        std::fs::write(format!("{root}/dir/sub/file.txt"), b"a").unwrap();
+
+        copy(format!("{root}/dir"), format!("{root}/copy")).unwrap();
+        assert_eq!(std::fs::read(format!("{root}/copy/sub/file.txt")).unwrap(), b"a");
+        copy(format!("{root}/copy/sub/file.txt"), format!("{root}/file.txt")).unwrap();
+
+        rename(format!("{root}/copy"), format!("{root}/moved")).unwrap();
+        assert!(!exists(format!("{root}/copy")));
+        assert!(exists(format!("{root}/moved/sub/file.txt")));
+
+        remove(format!("{root}/moved")).unwrap();
+        remove(format!("{root}/file.txt")).unwrap();
+        assert!(!exists(format!("{root}/moved")));
+        assert!(!exists(format!("{root}/file.txt")));
+        assert!(remove(format!("{root}/file.txt")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_symlinks_test() {
+        let root = scratch("copy_symlinks");
+        std::fs::create_dir_all(format!("{root}/dir/sub")).unwrap();
+        std::os::unix::fs::symlink("sub", format!("{root}/dir/to_sub")).unwrap();
+        std::os::unix::fs::symlink("missing.txt", format!("{root}/dir/broken")).unwrap();
+
+        copy(format!("{root}/dir"), format!("{root}/copy")).unwrap();
+        assert_eq!(std::fs::read_link(format!("{root}/copy/to_sub")).unwrap(), std::path::PathBuf::from("sub"));
+        assert_eq!(std::fs::read_link(format!("{root}/copy/broken")).unwrap(), std::path::PathBuf::from("missing.txt"));
+
+        copy(format!("{root}/dir/broken"), format!("{root}/broken")).unwrap();
+        assert!(std::fs::symlink_metadata(format!("{root}/broken")).unwrap().is_symlink());
+    }
+
+    #[test]
+    fn edit_test() {
+        let root = scratch("edit");
+        let path = format!("{root}/config.txt");
+        std::fs::write(&path, "a = 1").unwrap();
+
+        let mut file = EzFile::edit(&path, |text| text.replace('1', "2")).unwrap();
+        let mut buf = String::new();
+        file.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "a = 2");
+
+        let panicked = std::panic::catch_unwind(|| EzFile::edit(&path, |_| panic!("bad edit")));
+        assert!(panicked.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a = 2");
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 1);
+
+        std::fs::write(&path, [0xFF, 0xFE]).unwrap();
+        let err = EzFile::edit(&path, str::to_string).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn stats_by_extension_test() {
+        let root = scratch("stats_by_extension");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        std::fs::write(format!("{root}/a.png"), [0; 10]).unwrap();
+        std::fs::write(format!("{root}/sub/b.PNG"), [0; 5]).unwrap();
+        std::fs::write(format!("{root}/Makefile"), [0; 3]).unwrap();
+
+        let stats = EzDir::new(&root, false).unwrap().stats_by_extension();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats["png"], (2, 15));
+        assert_eq!(stats[""], (1, 3));
+    }
+
+    #[test]
+    fn cached_dir_test() {
+        let root = scratch("cached_dir");
+        std::fs::write(format!("{root}/a.txt"), b"aaaa").unwrap();
+        std::fs::write(format!("{root}/b.txt"), b"bbbb").unwrap();
+        std::fs::write(format!("{root}/big.txt"), [0; 64]).unwrap();
+
+        let dir = CachedEzDir::new(EzDir::new(&root, false).unwrap(), 8);
+        let a = dir.read_cached("a.txt").unwrap();
+        assert!(std::sync::Arc::ptr_eq(&a, &dir.read_cached("a.txt").unwrap()));
+        assert_eq!(dir.read_cached("big.txt").unwrap().len(), 64);
+
+        //a is still cached, changing it invalidates the entry
+        std::fs::write(format!("{root}/a.txt"), b"aaaaa").unwrap();
+        assert_eq!(&*dir.read_cached("a.txt").unwrap(), b"aaaaa");
+
+        //b doesn't fit next to a, so it pushes a out
+        let b = dir.read_cached("b.txt").unwrap();
+        assert!(std::sync::Arc::ptr_eq(&b, &dir.read_cached("b.txt").unwrap()));
+        let a = dir.read_cached("a.txt").unwrap();
+        assert!(!std::sync::Arc::ptr_eq(&b, &dir.read_cached("b.txt").unwrap()));
+        assert_eq!(&*a, b"aaaaa");
+
+        //nothing outside the directory
+        std::fs::write(format!("{root}.txt"), b"outside").unwrap();
+        let name = format!("../{}.txt", std::path::Path::new(&root).file_name().unwrap().to_str().unwrap());
+        assert_eq!(dir.read_cached(name).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(dir.read_cached(format!("{root}.txt")).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn append_test() {
+        let root = scratch("append");
+        let path = format!("{root}/log.txt");
+
+        EzFile::append(&path).unwrap().write_all(b"one\n").unwrap();
+        EzFile::append(&path).unwrap().write_all(b"two\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+
+        let mut file = EzFile::open(&path).unwrap();
+        file.to_append().unwrap();
+        file.write_all(b"three\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn open_rw_test() {
+        let root = scratch("rw");
+        let path = format!("{root}/data.txt");
+        std::fs::write(&path, "0123456789").unwrap();
+
+        let mut file = EzFile::create(&path).unwrap();
+        file.write_all(b"abcdef").unwrap();
+        file.to_rw().unwrap();
+        let mut buf = [0; 3];
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"abc");
+        file.write_all(b"X").unwrap();
+        file.read_exact(&mut buf[..2]).unwrap();
+        assert_eq!(&buf[..2], b"ef");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "abcXef");
+    }
+
+    #[test]
+    fn seek_test() {
+        use std::io::{Seek, SeekFrom};
+        let root = scratch("seek");
+        let mut file = EzFile::create(format!("{root}/hello.txt")).unwrap();
+        file.write_all(b"hello").unwrap();
+        file.to_read().unwrap();
+
+        let mut buf = String::new();
+        file.read_to_string(&mut buf).unwrap();
+        assert_eq!(file.seek(SeekFrom::Start(0)).unwrap(), 0);
+        buf.clear();
+        file.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "hello");
+
+        assert_eq!(file.seek(SeekFrom::End(-2)).unwrap(), 3);
+        assert_eq!(file.seek(SeekFrom::Current(-1)).unwrap(), 2);
+        assert_eq!(file.read_byte().unwrap(), Some(b'l'));
+    }
+
+    #[test]
+    fn len_test() {
+        let root = scratch("len");
+        let mut file = EzFile::create(format!("{root}/three.txt")).unwrap();
+        assert!(file.is_empty());
+        file.write_all(b"abc").unwrap();
+        file.refresh_metadata().unwrap();
+        assert_eq!(file.len(), 3);
+        assert!(!file.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path_test() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::Path};
+        let root = scratch("non_utf8");
+        let name = OsStr::from_bytes(b"bad\xff.txt");
+        std::fs::write(Path::new(&root).join(name), b"ok").unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(0);
+        let mut files = dir.flatten();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path().file_name(), Some(name));
+        assert_eq!(files[0].path_str(), None);
+        let mut buf = String::new();
+        files[0].read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "ok");
+    }
+
+    #[test]
+    fn error_test() {
+        let root = scratch("error");
+        let file = format!("{root}/file.txt");
+        std::fs::write(&file, b"").unwrap();
+
+        assert!(matches!(EzDir::new(&file, false), Err(EzError::NotADirectory(path)) if path == std::path::Path::new(&file)));
+        assert!(matches!(EzDir::new(format!("{root}/missing"), false), Err(EzError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound));
+        assert!(EzDir::new(&root, true).is_ok());
+
+        //still recoverable after going through io::Error
+        let err: std::io::Error = EzDir::new(&file, false).unwrap_err().into();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotADirectory);
+        assert!(matches!(err.get_ref().and_then(|e| e.downcast_ref::<EzError>()), Some(EzError::NotADirectory(_))));
+
+        let err: std::io::Error = EzError::from(std::io::Error::from(std::io::ErrorKind::NotFound)).into();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert!(err.get_ref().is_none());
+    }
+
+    #[test]
+    fn file_name_parts_test() {
+        let root = scratch("name_parts");
+        let parts = |name: &str| {
+            let file = EzFile::create(format!("{root}/{name}")).unwrap();
+            (file.file_name().map(String::from), file.stem().map(String::from), file.extension().map(String::from))
+        };
+        let owned = |name: &str, stem: &str, ext: Option<&str>| (Some(name.to_string()), Some(stem.to_string()), ext.map(String::from));
+
+        assert_eq!(parts("README"), owned("README", "README", None));
+        assert_eq!(parts(".gitignore"), owned(".gitignore", ".gitignore", None));
+        assert_eq!(parts("archive.tar.gz"), owned("archive.tar.gz", "archive.tar", Some("gz")));
+        assert_eq!(parts("main.rs"), owned("main.rs", "main", Some("rs")));
+    }
+
+    #[test]
+    fn filter_extension_test() {
+        let root = scratch("filter_extension");
+        std::fs::create_dir(format!("{root}/src")).unwrap();
+        for path in ["main.rs", "notes.txt", "src/lib.rs", "src/data.json", "src/old.RS", "rs"] {
+            std::fs::write(format!("{root}/{path}"), b"").unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(0);
+        let mut names: Vec<_> = dir.filter_extension("rs").iter().map(|f| f.file_name().unwrap().to_string()).collect();
+        names.sort();
+        assert_eq!(names, ["lib.rs", "main.rs"]);
+        assert_eq!(dir.filter_extension(".json").len(), 1);
+        assert!(dir.filter_extension("md").is_empty());
+        assert_eq!(dir.into_filter_extension("txt").len(), 1);
+    }
+
+    #[test]
+    fn find_test() {
+        let root = scratch("find");
+        std::fs::create_dir_all(format!("{root}/a/config")).unwrap();
+        std::fs::create_dir_all(format!("{root}/b")).unwrap();
+        for path in ["a/config/mod.rs", "b/mod.rs", "b/config"] {
+            std::fs::write(format!("{root}/{path}"), b"").unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        assert!(dir.find("mod.rs").is_none());
+        dir.walk(0);
+        assert!(dir.find("mod.rs").is_some_and(EzEntry::is_file));
+        assert!(dir.find("missing").is_none());
+        assert_eq!(dir.find_all("mod.rs").len(), 2);
+
+        let configs = dir.find_all("config");
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs.iter().filter(|e| e.is_dir()).count(), 1);
+    }
+
+    #[test]
+    fn glob_test() {
+        let root = scratch("glob");
+        std::fs::create_dir_all(format!("{root}/src/bin")).unwrap();
+        for path in ["build.rs", "src/lib.rs", "src/bin/main.rs", "src/bin/Tool.RS", "src/notes.txt"] {
+            std::fs::write(format!("{root}/{path}"), b"").unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(0);
+        let names = |pattern: &str| {
+            let mut names: Vec<_> = dir.glob(pattern).iter().map(|f| f.file_name().unwrap().to_string()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names("*.rs"), ["build.rs"]);
+        assert_eq!(names("src/**/*.rs"), ["lib.rs", "main.rs"]);
+        assert_eq!(names("**/*.rs"), ["build.rs", "lib.rs", "main.rs"]);
+        assert_eq!(names("src/bin/????.RS"), ["Tool.RS"]);
+        assert!(names("**/*.md").is_empty());
+
+        //patterns with many stars mustn't backtrack exponentially
+        std::fs::write(format!("{root}/{}", "a".repeat(64)), b"").unwrap();
+        dir.walk(0);
+        let count = |pattern: &str| dir.glob(pattern).len();
+        assert_eq!(count(&"*a".repeat(16)), 1);
+        assert_eq!(count(&format!("{}b", "*a".repeat(16))), 0);
+        assert_eq!(count(&format!("{}nope", "**/".repeat(16))), 0);
+    }
+
+    #[test]
+    fn sort_test() {
+        let root = scratch("sort");
+        std::fs::create_dir(format!("{root}/dir")).unwrap();
+        for (name, size) in [("b.txt", 3), ("a.txt", 3), ("file10", 1), ("file2", 2)] {
+            std::fs::write(format!("{root}/{name}"), vec![0; size]).unwrap();
+        }
+        let names = |dir: &EzDir| dir.iter().map(|e| e.to_string().rsplit('/').next().unwrap().to_string()).collect::<Vec<_>>();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.sort_by_name();
+        assert!(!dir.is_cached());
+
+        dir.cache().unwrap();
+        dir.sort_by_name();
+        assert_eq!(names(&dir), ["a.txt", "b.txt", "dir", "file10", "file2"]);
+        //stable, so equal sizes keep the name order
+        dir.sort_by_size();
+        assert_eq!(names(&dir), ["file10", "file2", "a.txt", "b.txt", "dir"]);
+        dir.sort_by(|a, b| b.is_dir().cmp(&a.is_dir()));
+        assert_eq!(names(&dir), ["dir", "file10", "file2", "a.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn total_size_test() {
+        let root = scratch("total_size");
+        std::fs::create_dir_all(format!("{root}/a/b")).unwrap();
+        std::fs::create_dir(format!("{root}/empty")).unwrap();
+        for (path, size) in [("one", 1), ("a/ten", 10), ("a/b/hundred", 100), ("a/b/zero", 0)] {
+            std::fs::write(format!("{root}/{path}"), vec![0; size]).unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, true).unwrap();
+        assert_eq!((dir.total_size(), dir.file_count()), (1, 1));
+        dir.walk(0);
+        assert_eq!((dir.total_size(), dir.file_count()), (111, 4));
+    }
+
+    #[test]
+    fn create_dir_test() {
+        let root = scratch("create_dir");
+        let dir = EzDir::create(format!("{root}/new")).unwrap();
+        assert_eq!((dir.len(), dir.total_len()), (Some(0), Some(0)));
+        assert!(EzDir::create(format!("{root}/new")).is_err());
+        assert!(EzDir::create(format!("{root}/missing/new")).is_err());
+
+        let dir = EzDir::create_all(format!("{root}/a/b/c")).unwrap();
+        assert_eq!(dir.len(), Some(0));
+        EzFile::create(dir.path().join("file.txt")).unwrap();
+        let dir = EzDir::create_all(format!("{root}/a/b/c")).unwrap();
+        assert_eq!(dir.len(), Some(1));
+        assert!(EzDir::create_all(format!("{root}/a/b/c/file.txt")).is_err());
+    }
+
+    #[test]
+    fn remove_test() {
+        let root = scratch("remove");
+        let exists = |path: &str| std::path::Path::new(&format!("{root}/{path}")).exists();
+        std::fs::create_dir_all(format!("{root}/full/sub")).unwrap();
+        std::fs::create_dir(format!("{root}/empty")).unwrap();
+        std::fs::write(format!("{root}/full/sub/a.txt"), b"a").unwrap();
+
+        let mut file = EzFile::create(format!("{root}/file.txt")).unwrap();
+        file.write_all(b"bar").unwrap();
+        file.remove().unwrap();
+        assert!(!exists("file.txt"));
+
+        EzDir::new(format!("{root}/empty"), false).unwrap().remove().unwrap();
+        assert!(!exists("empty"));
+
+        let mut full = EzDir::new(format!("{root}/full"), false).unwrap();
+        full.walk(0);
+        let full_path = full.path().to_owned();
+        assert!(EzDir::new(&full_path, false).unwrap().remove().is_err());
+        full.remove_all().unwrap();
+        assert!(!exists("full"));
+    }
+
+    #[test]
+    fn rename_test() {
+        let root = scratch("rename");
+        let mut file = EzFile::create(format!("{root}/old.txt")).unwrap();
+        file.write_all(b"bar").unwrap();
+        file.rename(format!("{root}/new.txt")).unwrap();
+        assert!(!std::path::Path::new(&format!("{root}/old.txt")).exists());
+        assert_eq!(file.file_name(), Some("new.txt"));
+        assert_eq!(file.len(), 3);
+        file.to_read().unwrap();
+        let mut buf = String::new();
+        file.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "bar");
+
+        std::fs::create_dir_all(format!("{root}/dir/sub")).unwrap();
+        std::fs::write(format!("{root}/dir/sub/a.txt"), b"a").unwrap();
+        let mut dir = EzDir::new(format!("{root}/dir"), false).unwrap();
+        dir.walk(0);
+        dir.rename(format!("{root}/moved")).unwrap();
+        assert!(!std::path::Path::new(&format!("{root}/dir")).exists());
+        let files = dir.flatten();
+        assert_eq!(files[0].path(), std::path::Path::new(&format!("{root}/moved/sub/a.txt")));
+        assert_eq!(std::fs::read(files[0].path()).unwrap(), b"a");
+    }
+
+    #[test]
+    fn copy_to_test() {
+        let root = scratch("copy_to");
+        let file = EzFile::write_then_read(format!("{root}/a.txt"), b"hello").unwrap();
+
+        let mut copy = file.copy_to(format!("{root}/b.txt")).unwrap();
+        assert_eq!(copy.len(), file.len());
+        let mut buf = String::new();
+        copy.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "hello");
+
+        let same = file.copy_to(format!("{root}/./a.txt")).unwrap();
+        assert_eq!(same.len(), 5);
+        assert_eq!(std::fs::read(format!("{root}/a.txt")).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn dir_copy_to_test() {
+        let root = scratch("dir_copy_to");
+        std::fs::create_dir_all(format!("{root}/src/a/b")).unwrap();
+        for path in ["one.txt", "a/two.txt", "a/b/three.txt"] {
+            std::fs::write(format!("{root}/src/{path}"), path).unwrap();
+        }
+
+        let mut src = EzDir::new(format!("{root}/src"), false).unwrap();
+        src.walk(0);
+        let copy = src.copy_to(format!("{root}/nested/copy")).unwrap();
+        assert!(copy.is_cached());
+        assert_eq!(std::fs::read_to_string(format!("{root}/nested/copy/a/b/three.txt")).unwrap(), "a/b/three.txt");
+        assert_eq!(copy.flatten_all().len(), src.flatten_all().len());
+
+        //only the cached level gets copied
+        let shallow = EzDir::new(format!("{root}/src"), true).unwrap().copy_to(format!("{root}/shallow")).unwrap();
+        assert_eq!(shallow.len(), Some(2));
+        assert_eq!(shallow.flatten_all().len(), 1);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn rayon_walk_test() {
+        let root = scratch("rayon_walk");
+        for i in 0..16 {
+            for j in 0..4 {
+                std::fs::create_dir_all(format!("{root}/{i}/{j}/deep")).unwrap();
+                std::fs::write(format!("{root}/{i}/{j}/deep/file.txt"), b"").unwrap();
+            }
+        }
+        let sorted_paths = |dir: EzDir| {
+            let mut paths: Vec<_> = dir.flatten().into_iter().map(|f| f.path().to_owned()).collect();
+            paths.sort();
+            paths
+        };
+
+        let mut serial = EzDir::new(&root, false).unwrap();
+        serial.walk(2);
+        let mut parallel = EzDir::new(&root, false).unwrap();
+        parallel.walk_parallel(2);
+        assert_eq!(parallel.total_len(), serial.total_len());
+        assert_eq!(parallel.file_count(), 0);
+
+        serial.walk(0);
+        parallel.walk_parallel(0);
+        assert_eq!(parallel.total_len(), serial.total_len());
+        assert_eq!(sorted_paths(parallel), sorted_paths(serial));
+    }
+
+    #[test]
+    fn walk_iter_test() {
+        let root = scratch("walk_iter");
+        std::fs::create_dir_all(format!("{root}/a/b/c")).unwrap();
+        std::fs::create_dir(format!("{root}/empty")).unwrap();
+        for path in ["one", "a/two", "a/b/three", "a/b/c/four"] {
+            std::fs::write(format!("{root}/{path}"), b"").unwrap();
+        }
+
+        let mut names: Vec<_> = EzDir::new(&root, false).unwrap().walk_iter()
+            .map(|file| file.unwrap().file_name().unwrap().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["four", "one", "three", "two"]);
+
+        let mut walked = EzDir::new(&root, false).unwrap();
+        walked.walk(0);
+        assert_eq!(walked.walk_iter().count(), 4);
+    }
+
+    #[test]
+    fn flatten_depth_test() {
+        let root = scratch("flatten_depth");
+        std::fs::create_dir_all(format!("{root}/a/b")).unwrap();
+        for path in ["top", "a/mid", "a/b/deep"] {
+            std::fs::write(format!("{root}/{path}"), b"").unwrap();
+        }
+        let names = |depth: usize| {
+            let mut dir = EzDir::new(&root, false).unwrap();
+            dir.walk(0);
+            let mut names: Vec<_> = dir.flatten_depth(depth).iter().map(|f| f.file_name().unwrap().to_string()).collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(1), ["mid", "top"]);
+        assert_eq!(names(2), ["deep", "mid", "top"]);
+        assert_eq!(names(0), ["deep", "mid", "top"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_entry_test() {
+        let root = scratch("symlink_entry");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        std::fs::write(format!("{root}/sub/real.txt"), b"real").unwrap();
+        std::os::unix::fs::symlink("real.txt", format!("{root}/sub/link.txt")).unwrap();
+        //points back up the tree, walking must not follow it
+        std::os::unix::fs::symlink("..", format!("{root}/sub/loop")).unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(0);
+        assert_eq!(dir.total_len(), Some(4));
+        assert_eq!(dir.file_count(), 1);
+        let links = dir.find_all("loop");
+        assert!(links.len() == 1 && links[0].is_symlink());
+
+        let _ = std::fs::remove_dir_all(format!("{root}_copy"));
+        let copy = dir.copy_to(format!("{root}_copy")).unwrap();
+        assert_eq!(copy.total_len(), Some(4));
+        assert_eq!(std::fs::read_link(format!("{root}_copy/sub/link.txt")).unwrap(), std::path::Path::new("real.txt"));
+        assert_eq!(std::fs::read(format!("{root}_copy/sub/link.txt")).unwrap(), b"real");
+        copy.remove_all().unwrap();
+    }
+
+    #[test]
+    fn ignore_hidden_test() {
+        let root = scratch("ignore_hidden");
+        std::fs::create_dir_all(format!("{root}/.git/objects")).unwrap();
+        std::fs::create_dir(format!("{root}/src")).unwrap();
+        for path in [".hidden", "visible", "src/.DS_Store", "src/lib.rs", ".git/objects/abc"] {
+            std::fs::write(format!("{root}/{path}"), b"").unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        assert!(!dir.ignores_hidden());
+        dir.walk(0);
+        assert_eq!(dir.file_count(), 5);
+
+        dir.set_ignore_hidden(true);
+        dir.walk(0);
+        let mut names: Vec<_> = dir.iter_files().map(|f| f.file_name().unwrap().to_string()).collect();
+        names.sort();
+        assert_eq!(names, ["lib.rs", "visible"]);
+        assert!(dir.find(".git").is_none());
+        assert_eq!(dir.walk_iter().count(), 2);
+    }
+
+    #[test]
+    fn parent_test() {
+        use std::path::Path;
+        let parent = |path: &str| EzDir::new(path, false).unwrap().parent().map(|p| p.path().to_owned());
+
+        assert_eq!(parent("src").as_deref(), Some(Path::new(".")));
+        assert_eq!(parent("src/").as_deref(), Some(Path::new(".")));
+        assert_eq!(parent("./src").as_deref(), Some(Path::new(".")));
+        assert_eq!(parent(".").as_deref(), Some(Path::new("..")));
+        assert_eq!(parent("..").as_deref(), Some(Path::new("../..")));
+
+        let root = scratch("parent");
+        std::fs::create_dir(format!("{root}/child")).unwrap();
+        assert_eq!(parent(&format!("{root}/child")).as_deref(), Some(Path::new(&root)));
+        #[cfg(unix)]
+        assert_eq!(parent("/"), None);
+
+        let mut dir = EzDir::new("src", false).unwrap().parent().unwrap();
+        dir.cache().unwrap();
+        assert!(dir.find("Cargo.toml").is_some());
+    }
+
+    #[test]
+    fn child_test() {
+        let root = scratch("child");
+        std::fs::create_dir_all(format!("{root}/a/b")).unwrap();
+        std::fs::write(format!("{root}/a/b/file.txt"), b"bar").unwrap();
+
+        let dir = EzDir::new(&root, false).unwrap();
+        let b = dir.child("a").unwrap().child("b").unwrap();
+        assert!(!b.is_cached());
+        let mut buf = String::new();
+        b.open_file("file.txt").unwrap().read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "bar");
+
+        assert!(matches!(dir.child("missing"), Err(EzError::Io(_))));
+        assert!(matches!(b.child("file.txt"), Err(EzError::NotADirectory(_))));
+        assert!(dir.open_file("missing.txt").is_err());
+        assert_eq!(dir.open_file("a").unwrap_err().kind(), std::io::ErrorKind::IsADirectory);
+    }
+
+    #[test]
+    fn index_dir_test() {
+        let root = scratch("index_dir");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+
+        let mut dir = EzDir::new(&root, true).unwrap();
+        assert!(dir[0].is_dir());
+        if let EzEntry::Dir(sub) = &mut dir[0] {
+            sub.cache().unwrap();
+        }
+        assert_eq!(dir.get(0).map(EzEntry::is_dir), Some(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "has not been cached")]
+    fn index_uncached_test() {
+        let dir = EzDir::new(".", false).unwrap();
+        let _ = &dir[0];
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn index_out_of_bounds_test() {
+        let root = scratch("index_out_of_bounds");
+        let dir = EzDir::new(&root, true).unwrap();
+        let _ = &dir[0];
+    }
+
+    #[test]
+    fn entry_accessors_test() {
+        let root = scratch("entry_accessors");
+        std::fs::create_dir(format!("{root}/dir")).unwrap();
+        std::fs::write(format!("{root}/file"), b"").unwrap();
+
+        let mut dir = EzDir::new(&root, true).unwrap();
+        dir.sort_by_name();
+        assert!(dir[0].as_dir().is_some() && dir[0].as_file().is_none());
+        assert!(dir[1].as_file().is_some() && dir[1].as_dir().is_none());
+
+        dir[0].as_dir_mut().unwrap().cache().unwrap();
+        assert!(dir[0].as_dir().unwrap().is_cached());
+        dir[1].as_file_mut().unwrap().to_write().unwrap();
+
+        let mut entries = dir.into_iter();
+        let (sub, file) = (entries.next().unwrap(), entries.next().unwrap());
+        assert_eq!(sub.into_dir().unwrap().len(), Some(0));
+        assert_eq!(file.into_file().unwrap().file_name(), Some("file"));
+    }
+
+    #[test]
+    fn entry_name_test() {
+        let root = scratch("entry_name");
+        std::fs::create_dir(format!("{root}/dir")).unwrap();
+        std::fs::write(format!("{root}/a.txt"), b"").unwrap();
+        std::fs::write(format!("{root}/.hidden"), b"").unwrap();
+
+        let dir = EzDir::new(&root, true).unwrap();
+        let mut names: Vec<_> = dir.iter().map(|e| e.name().unwrap()).collect();
+        names.sort_unstable();
+        assert_eq!(names, [".hidden", "a.txt", "dir"]);
+        for entry in &dir {
+            assert_eq!(entry.path(), std::path::Path::new(&root).join(entry.name().unwrap()));
+        }
+    }
+
+    #[test]
+    fn set_permissions_test() {
+        let root = scratch("set_permissions");
+        let mut file = EzFile::create(format!("{root}/file.txt")).unwrap();
+        assert!(!file.permissions().readonly());
+
+        file.set_readonly(true).unwrap();
+        assert!(file.permissions().readonly());
+        assert!(std::fs::metadata(file.path()).unwrap().permissions().readonly());
+        file.set_readonly(false).unwrap();
+        assert!(!file.permissions().readonly());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_mode(0o640).unwrap();
+            assert_eq!(file.permissions().mode() & 0o777, 0o640);
+        }
+    }
+
+    #[test]
+    fn read_all_test() {
+        let root = scratch("read_all");
+        let mut file = EzFile::create(format!("{root}/lines.txt")).unwrap();
+        file.write_all(b"first\nsecond\r\n\nlast").unwrap();
+        file.to_read().unwrap();
+
+        let lines: Vec<_> = file.lines().unwrap().collect::<Result<_, EzError>>().unwrap();
+        assert_eq!(lines, ["first", "second", "", "last"]);
+        //every reader starts over from the top
+        assert_eq!(file.lines().unwrap().count(), 4);
+        assert_eq!(file.read_all_string().unwrap(), "first\nsecond\r\n\nlast");
+        assert_eq!(file.read_all_bytes().unwrap().len(), 19);
+    }
+
+    #[test]
+    fn write_lines_test() {
+        let root = scratch("write_lines");
+        let mut file = EzFile::create(format!("{root}/lines.txt")).unwrap();
+        file.write_line("one").unwrap();
+        file.write_lines(vec![String::from("two"), String::from("three")]).unwrap();
+        file.write_lines(std::iter::empty::<&str>()).unwrap();
+
+        file.to_read().unwrap();
+        let lines: Vec<_> = file.lines().unwrap().map(Result::unwrap).collect();
+        assert_eq!(lines, ["one", "two", "three"]);
+        assert_eq!(file.read_all_string().unwrap(), "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn truncate_test() {
+        let root = scratch("truncate");
+        let mut file = EzFile::create(format!("{root}/file.txt")).unwrap();
+        file.write_all(b"0123456789").unwrap();
+        file.truncate(4).unwrap();
+        file.refresh_metadata().unwrap();
+        assert_eq!(file.len(), 4);
+        file.truncate(6).unwrap();
+        assert_eq!(std::fs::read(file.path()).unwrap(), b"0123\0\0");
+
+        file.to_read().unwrap();
+        let err = file.truncate(0).unwrap_err();
+        assert!(err.to_string().contains("must be writable"));
+        assert_eq!(file.len(), 6);
+    }
+
+    #[test]
+    fn hash_test() {
+        use std::io::{Seek, SeekFrom};
+        let root = scratch("hash");
+        let mut a = EzFile::write_then_read(format!("{root}/a"), &[7; 20_000]).unwrap();
+        let mut b = EzFile::write_then_read(format!("{root}/b"), &[7; 20_000]).unwrap();
+        let mut c = EzFile::write_then_read(format!("{root}/c"), &[8; 20_000]).unwrap();
+
+        a.seek(SeekFrom::Start(5)).unwrap();
+        assert_eq!(a.hash().unwrap(), b.hash().unwrap());
+        assert_ne!(a.hash().unwrap(), c.hash().unwrap());
+        assert_eq!(a.stream_position().unwrap(), 5);
+
+        #[cfg(feature = "sha256")]
+        assert_eq!(
+            EzFile::write_then_read(format!("{root}/d"), b"abc").unwrap().sha256_hex().unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        );
+    }
+
+    #[test]
+    fn find_duplicates_test() {
+        let root = scratch("find_duplicates");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        std::fs::write(format!("{root}/a"), "same").unwrap();
+        std::fs::write(format!("{root}/sub/b"), "same").unwrap();
+        std::fs::write(format!("{root}/c"), "diff").unwrap();
+        std::fs::write(format!("{root}/d"), "same but longer").unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        let dupes = dir.find_duplicates().unwrap();
+        assert_eq!(dupes, [[std::path::PathBuf::from(format!("{root}/a")), std::path::PathBuf::from(format!("{root}/sub/b"))]]);
+    }
+
+    #[test]
+    fn entries_test() {
+        let root = scratch("entries");
+        for name in ["c", "a", "b"] {
+            std::fs::write(format!("{root}/{name}"), name).unwrap();
+        }
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        assert!(dir.entries().is_none());
+        assert!(dir.entries_mut().is_none());
+
+        dir.cache().unwrap();
+        dir.sort_by_name();
+        let names: Vec<_> = dir.entries().unwrap().iter().map(|e| e.name().unwrap()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+
+        dir.entries_mut().unwrap().swap(0, 2);
+        assert_eq!(dir[0].name(), Some("c"));
+    }
+
+    #[test]
+    fn cache_error_test() {
+        let root = scratch("cache_error");
+        std::fs::create_dir(format!("{root}/gone")).unwrap();
+
+        let mut dir = EzDir::new(format!("{root}/gone"), false).unwrap();
+        std::fs::remove_dir(format!("{root}/gone")).unwrap();
+        assert_eq!(dir.cache().unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        assert!(!dir.is_cached());
+    }
+
+    #[test]
+    fn try_walk_test() {
+        let root = scratch("try_walk");
+        std::fs::create_dir(format!("{root}/gone")).unwrap();
+        let mut dir = EzDir::new(format!("{root}/gone"), false).unwrap();
+        std::fs::remove_dir(format!("{root}/gone")).unwrap();
+        assert!(dir.try_walk(0).is_err());
+        assert_eq!(dir.walk_collecting_errors(0).len(), 1);
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.try_walk(0).unwrap();
+        assert!(dir.walk_collecting_errors(0).is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn try_walk_unreadable_test() {
+        use std::os::unix::fs::PermissionsExt;
+        let root = scratch("try_walk_unreadable");
+        std::fs::create_dir_all(format!("{root}/locked/inner")).unwrap();
+        std::fs::create_dir(format!("{root}/open")).unwrap();
+        std::fs::set_permissions(format!("{root}/locked"), std::fs::Permissions::from_mode(0o000)).unwrap();
+        //permission bits don't stop root
+        let readable = std::fs::read_dir(format!("{root}/locked")).is_ok();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        let errors = dir.walk_collecting_errors(0);
+        let result = dir.try_walk(0);
+        dir.walk(0);
+        std::fs::set_permissions(format!("{root}/locked"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        if readable {
+            return
+        }
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), std::io::ErrorKind::PermissionDenied);
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::PermissionDenied);
+        //the lossy walk still caches everything else
+        assert!(dir.find("open").unwrap().as_dir().unwrap().is_cached());
+        assert!(!dir.find("locked").unwrap().as_dir().unwrap().is_cached());
+    }
+
+    #[test]
+    fn tree_string_test() {
+        let root = scratch("tree_string");
+        std::fs::create_dir_all(format!("{root}/b/d")).unwrap();
+        std::fs::write(format!("{root}/a.txt"), "").unwrap();
+        std::fs::write(format!("{root}/b/c.txt"), "").unwrap();
+        std::fs::write(format!("{root}/b/d/e.txt"), "").unwrap();
+        std::fs::write(format!("{root}/f.txt"), "").unwrap();
+
+        fn sort(dir: &mut EzDir) {
+            dir.sort_by_name();
+            for entry in dir.iter_mut() {
+                if let Some(d) = entry.as_dir_mut() {
+                    sort(d);
+                }
+            }
+        }
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(0);
+        sort(&mut dir);
+        assert_eq!(dir.tree_string(), format!("\
+{root}
+├── a.txt
+├── b/
+│   ├── c.txt
+│   └── d/
+│       └── e.txt
+└── f.txt
+"));
+    }
+
+    #[test]
+    fn from_files_test() {
+        let root = scratch("from_files");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        let a = EzFile::write_then_read(format!("{root}/a.txt"), b"a").unwrap();
+        let b = EzFile::write_then_read(format!("{root}/sub/b.txt"), b"bb").unwrap();
+
+        let group = EzDir::from_files("group", [a, b]);
+        assert_eq!(group.path(), std::path::Path::new("group"));
+        assert_eq!(group.total_len(), Some(2));
+        assert_eq!(group.total_size(), 3);
+
+        let names: Vec<_> = group.flatten().iter().map(|f| f.path().to_owned()).collect();
+        assert_eq!(names, [std::path::PathBuf::from(format!("{root}/a.txt")), std::path::PathBuf::from(format!("{root}/sub/b.txt"))]);
+    }
+
+    #[test]
+    fn eq_test() {
+        let root = scratch("eq");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        std::fs::write(format!("{root}/a"), "a").unwrap();
+        std::fs::write(format!("{root}/b"), "b").unwrap();
+
+        let a = EzEntry::File(EzFile::open(format!("{root}/a")).unwrap());
+        let a2 = EzEntry::File(EzFile::open(format!("{root}/a")).unwrap());
+        let b = EzEntry::File(EzFile::open(format!("{root}/b")).unwrap());
+        assert_eq!(a, a2);
+        assert_ne!(a, b);
+
+        //same path, different kind
+        let sub = EzEntry::Dir(EzDir::new(format!("{root}/sub"), false).unwrap());
+        assert_ne!(sub, EzEntry::Symlink(format!("{root}/sub").into()));
+
+        //cached or not doesn't matter
+        let mut dir = EzDir::new(&root, true).unwrap();
+        assert_eq!(dir, EzDir::new(&root, false).unwrap());
+        dir.sort_by_name();
+        assert_eq!(dir.get(0), Some(&a));
+    }
+
+    #[test]
+    fn exists_test() {
+        let root = scratch("exists");
+        std::fs::write(format!("{root}/file"), "").unwrap();
+
+        assert!(EzFile::exists(format!("{root}/file")));
+        assert!(!EzFile::exists(&root));
+        assert!(!EzFile::exists(format!("{root}/missing")));
+
+        assert!(EzDir::exists(&root));
+        assert!(!EzDir::exists(format!("{root}/file")));
+        assert!(!EzDir::exists(format!("{root}/missing")));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_test() {
+        let root = scratch("serde");
+        std::fs::create_dir_all(format!("{root}/sub/deep")).unwrap();
+        std::fs::write(format!("{root}/a.txt"), "a").unwrap();
+        std::fs::write(format!("{root}/sub/b.txt"), "b").unwrap();
+        std::fs::write(format!("{root}/sub/deep/c.txt"), "c").unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(1);
+        let json = serde_json::to_string(&dir).unwrap();
+        let rebuilt: EzDir = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(rebuilt, dir);
+        assert_eq!(rebuilt.total_len(), None);
+        let deep = rebuilt.find("deep").unwrap().as_dir().unwrap();
+        assert!(!deep.is_cached());
+
+        let paths = |dir: EzDir| {
+            let mut paths: Vec<_> = dir.flatten().iter().map(|f| f.path().to_owned()).collect();
+            paths.sort();
+            paths
+        };
+        let expected = paths(dir);
+        assert_eq!(expected.len(), 2);
+        assert_eq!(paths(rebuilt), expected);
+
+        //a file that's gone fails the whole thing instead of going missing
+        std::fs::remove_file(format!("{root}/a.txt")).unwrap();
+        assert!(serde_json::from_str::<EzDir>(&json).is_err());
+    }
+
+    #[test]
+    fn metadata_test() {
+        let root = scratch("metadata");
+        let mut file = EzFile::write_then_read(format!("{root}/a"), b"hello").unwrap();
+        assert_eq!(file.metadata().len(), file.len());
+        assert_eq!(file.metadata().len(), 5);
+
+        assert!(!file.is_readonly());
+        file.set_readonly(true).unwrap();
+        assert!(file.is_readonly());
+        file.set_readonly(false).unwrap();
+    }
+
+    #[test]
+    fn open_with_retries_test() {
+        let root = scratch("open_with_retries");
+        std::fs::write(format!("{root}/a"), "a").unwrap();
+        assert!(EzFile::open_with_retries(format!("{root}/a"), 3).is_ok());
+
+        //20 retries would back off for hours, so this only passes if NotFound isn't retried
+        let start = std::time::Instant::now();
+        let err = EzFile::open_with_retries(format!("{root}/missing"), 20).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn refresh_test() {
+        let root = scratch("refresh");
+        std::fs::create_dir(format!("{root}/sub")).unwrap();
+        std::fs::write(format!("{root}/sub/inner"), "").unwrap();
+        std::fs::write(format!("{root}/old"), "").unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(0);
+        let names = |dir: &EzDir| {
+            let mut names: Vec<_> = dir.iter().map(|e| e.name().unwrap().to_owned()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&dir), ["old", "sub"]);
+
+        std::fs::write(format!("{root}/new"), "").unwrap();
+        std::fs::remove_file(format!("{root}/old")).unwrap();
+        dir.refresh().unwrap();
+        assert_eq!(names(&dir), ["new", "sub"]);
+        //the walked subtree survives
+        assert!(dir.find("inner").is_some());
+        assert_eq!(dir.total_len(), Some(3));
+
+        std::fs::remove_file(format!("{root}/new")).unwrap();
+        dir.refresh().unwrap();
+        assert_eq!(names(&dir), ["sub"]);
+    }
+
+    #[test]
+    fn retain_test() {
+        let root = scratch("retain");
+        std::fs::create_dir_all(format!("{root}/a/b")).unwrap();
+        std::fs::write(format!("{root}/a/inner"), "").unwrap();
+        std::fs::write(format!("{root}/x"), "").unwrap();
+        std::fs::write(format!("{root}/y"), "").unwrap();
+
+        let mut uncached = EzDir::new(&root, false).unwrap();
+        uncached.retain(|_| false);
+        assert!(!uncached.is_cached());
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(0);
+        assert_eq!(dir.total_len(), Some(5));
+        dir.retain(EzEntry::is_dir);
+        assert!(dir.iter().all(|e| !matches!(e, EzEntry::File(_))));
+        assert_eq!(dir.len(), Some(1));
+        //only this level is filtered
+        assert_eq!(dir.total_len(), Some(3));
+    }
+
+    #[test]
+    fn count_where_test() {
+        let root = scratch("count_where");
+        std::fs::create_dir_all(format!("{root}/a/b")).unwrap();
+        for name in ["one.txt", "a/two.txt", "a/b/three.txt", "a/b/other.md"] {
+            std::fs::write(format!("{root}/{name}"), "").unwrap();
+        }
+        let txt = |e: &EzEntry| e.path().extension().is_some_and(|ext| ext == "txt");
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        assert_eq!(dir.count_where(txt), 0);
+        assert!(!dir.any(EzEntry::is_dir));
+        assert!(dir.all(EzEntry::is_dir));
+
+        dir.walk(0);
+        assert_eq!(dir.count_where(txt), 3);
+        assert_eq!(dir.count_where(|_| true), dir.total_len().unwrap());
+        assert!(dir.any(EzEntry::is_dir));
+        assert!(!dir.all(EzEntry::is_file));
+        assert!(dir.all(|e| !e.is_symlink()));
+    }
+
+    #[test]
+    fn drop_flush_test() {
+        let root = scratch("drop_flush");
+        let mut file = EzFile::create(format!("{root}/a")).unwrap();
+        file.write_all(b"written before drop").unwrap();
+        drop(file);
+
+        let mut file = EzFile::open(format!("{root}/a")).unwrap();
+        assert_eq!(file.read_all_string().unwrap(), "written before drop");
+    }
+
+    #[test]
+    fn sync_test() {
+        let root = scratch("sync");
+        let mut file = EzFile::create(format!("{root}/a")).unwrap();
+        file.write_all(b"durable").unwrap();
+        file.sync_data().unwrap();
+        file.sync_all().unwrap();
+
+        let mut file = EzFile::open(format!("{root}/a")).unwrap();
+        assert_eq!(file.read_all_string().unwrap(), "durable");
+        //read-only handles can be synced too
+        file.sync_all().unwrap();
+    }
+
+    #[test]
+    fn write_atomic_test() {
+        let root = scratch("write_atomic");
+        std::fs::write(format!("{root}/a"), "old contents that are longer").unwrap();
+
+        let mut file = EzFile::write_atomic(format!("{root}/a"), b"new").unwrap();
+        assert_eq!(file.read_all_string().unwrap(), "new");
+        assert_eq!(std::fs::read_to_string(format!("{root}/a")).unwrap(), "new");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(format!("{root}/a"), std::fs::Permissions::from_mode(0o600)).unwrap();
+            EzFile::write_atomic(format!("{root}/a"), b"secret").unwrap();
+            let mode = std::fs::metadata(format!("{root}/a")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let names: Vec<_> = std::fs::read_dir(&root).unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(names, ["a"]);
+    }
+
+    #[test]
+    fn walk_with_test() {
+        let root = scratch("walk_with");
+        std::fs::create_dir_all(format!("{root}/a/b")).unwrap();
+        std::fs::create_dir(format!("{root}/c")).unwrap();
+        std::fs::write(format!("{root}/a/file"), "").unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        let mut visited = vec![];
+        dir.walk_with(0, |path, depth| visited.push((path.to_owned(), depth)));
+        visited.sort();
+        let expected: Vec<_> = [("", 0), ("/a", 1), ("/a/b", 2), ("/c", 1)].iter()
+            .map(|(p, d)| (std::path::PathBuf::from(format!("{root}{p}")), *d))
+            .collect();
+        assert_eq!(visited, expected);
+
+        let mut plain = EzDir::new(&root, false).unwrap();
+        plain.walk(0);
+        assert_eq!(dir.total_len(), plain.total_len());
+
+        let mut visited = 0;
+        EzDir::new(&root, false).unwrap().walk_with(1, |_, _| visited += 1);
+        assert_eq!(visited, 3);
+    }
+
+    #[test]
+    fn try_clone_test() {
+        use std::io::{Read, Seek};
+        let root = scratch("try_clone");
+        let mut file = EzFile::write_then_read(format!("{root}/a"), b"abcdef").unwrap();
+        let mut buf = [0; 2];
+        file.read_exact(&mut buf).unwrap();
+
+        let mut a = file.try_clone().unwrap();
+        let mut b = file.try_clone().unwrap();
+        a.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ab");
+        b.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ab");
+
+        //a shared handle moves with the original
+        let mut shared = file.try_clone_handle().unwrap();
+        shared.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"cd");
+        assert_eq!(file.stream_position().unwrap(), 4);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn flatten_parallel_test() {
+        fn send_sync<T: Send + Sync>() {}
+        send_sync::<EzFile>();
+        send_sync::<EzDir>();
+        send_sync::<EzEntry>();
+
+        let root = scratch("flatten_parallel");
+        for d in ["a", "a/b", "c"] {
+            std::fs::create_dir_all(format!("{root}/{d}")).unwrap();
+            for f in 0..5 {
+                std::fs::write(format!("{root}/{d}/{f}"), "").unwrap();
+            }
+        }
+
+        let paths = |files: Vec<EzFile>| {
+            let mut paths: Vec<_> = files.iter().map(|f| f.path().to_owned()).collect();
+            paths.sort();
+            paths
+        };
+        let serial = paths(EzDir::new(&root, false).unwrap().flatten_all());
+        assert_eq!(serial.len(), 15);
+        assert_eq!(paths(EzDir::new(&root, false).unwrap().flatten_parallel()), serial);
+    }
+
+    #[test]
+    fn diff_test() {
+        let root = scratch("diff");
+        for tree in ["a", "b"] {
+            std::fs::create_dir_all(format!("{root}/{tree}/sub")).unwrap();
+            std::fs::write(format!("{root}/{tree}/sub/shared"), "").unwrap();
+        }
+        std::fs::write(format!("{root}/a/only_a"), "").unwrap();
+        std::fs::write(format!("{root}/b/sub/only_b"), "").unwrap();
+
+        let mut a = EzDir::new(format!("{root}/a"), false).unwrap();
+        let mut b = EzDir::new(format!("{root}/b"), false).unwrap();
+        a.walk(0);
+        b.walk(0);
+        let diff = a.diff(&b);
+        assert_eq!(diff.only_in_self, ["only_a"]);
+        assert_eq!(diff.only_in_other, ["sub/only_b"]);
+        assert_eq!(diff.in_both, ["sub", "sub/shared"]);
+        assert_eq!(b.diff(&a).only_in_self, ["sub/only_b"]);
+    }
+
+    #[test]
+    fn with_options_test() {
+        let root = scratch("with_options");
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        let mut file = EzFile::with_options(format!("{root}/a"), &options).unwrap();
+        file.write_all(b"first").unwrap();
+        let err = EzFile::with_options(format!("{root}/a"), &options).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(format!("{root}/a")).unwrap(), "first");
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn read_at_test() {
+        let root = scratch("read_at");
+        let data: Vec<u8> = (0..=255).collect();
+        let file = EzFile::write_then_read(format!("{root}/a"), &data).unwrap();
+
+        let mut buf = [0; 16];
+        assert_eq!(file.read_at(100, &mut buf).unwrap(), 16);
+        assert_eq!(buf[..], data[100..116]);
+        //short read at the end
+        assert_eq!(file.read_at(250, &mut buf).unwrap(), 6);
+        assert_eq!(file.read_at(300, &mut buf).unwrap(), 0);
+
+        #[cfg(unix)]
+        {
+            use std::io::{Read, Seek, SeekFrom};
+            let mut file = file;
+            file.seek(SeekFrom::Start(0)).unwrap();
+            file.read_at(100, &mut buf).unwrap();
+            let mut first = [0; 1];
+            file.read_exact(&mut first).unwrap();
+            assert_eq!(first[0], 0);
+        }
+    }
+
+    #[test]
+    fn is_hidden_test() {
+        let root = scratch("is_hidden");
+        std::fs::write(format!("{root}/.dotfile"), "").unwrap();
+        std::fs::write(format!("{root}/visible"), "").unwrap();
+        std::fs::create_dir(format!("{root}/.dotdir")).unwrap();
+
+        assert!(EzFile::open(format!("{root}/.dotfile")).unwrap().is_hidden());
+        assert!(!EzFile::open(format!("{root}/visible")).unwrap().is_hidden());
+
+        let mut dir = EzDir::new(&root, true).unwrap();
+        dir.sort_by_name();
+        let hidden: Vec<_> = dir.iter().map(EzEntry::is_hidden).collect();
+        assert_eq!(hidden, [true, true, false]);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn is_hidden_attribute_test() {
+        use std::os::windows::fs::OpenOptionsExt;
+        let root = scratch("is_hidden_attribute");
+        std::fs::OpenOptions::new().write(true).create(true)
+            .attributes(0x2) //FILE_ATTRIBUTE_HIDDEN
+            .open(format!("{root}/secret")).unwrap();
+
+        assert!(EzFile::open(format!("{root}/secret")).unwrap().is_hidden());
+        assert!(EzDir::new(&root, true).unwrap()[0].is_hidden());
+    }
+
+    #[test]
+    fn append_bytes_test() {
+        use std::io::{Seek, SeekFrom};
+        let root = scratch("append_bytes");
+        let mut file = EzFile::write_then_read(format!("{root}/a"), b"start").unwrap();
+        file.append_bytes(b" middle").unwrap();
+        //writes go to the end even after seeking back
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.append_bytes(b" end").unwrap();
+        assert_eq!(std::fs::read_to_string(format!("{root}/a")).unwrap(), "start middle end");
+
+        let mut log = EzFile::append(format!("{root}/b")).unwrap();
+        log.append_bytes(b"x").unwrap();
+        log.append_bytes(b"y").unwrap();
+        assert_eq!(std::fs::read_to_string(format!("{root}/b")).unwrap(), "xy");
+    }
+
+    #[test]
+    fn entry_open_test() {
+        let root = scratch("entry_open");
+        std::fs::create_dir(format!("{root}/dir")).unwrap();
+        std::fs::write(format!("{root}/file"), "").unwrap();
+
+        assert!(matches!(EzEntry::open(format!("{root}/file")).unwrap(), EzEntry::File(_)));
+        let dir = EzEntry::open(format!("{root}/dir")).unwrap();
+        assert!(matches!(&dir, EzEntry::Dir(d) if !d.is_cached()));
+        assert_eq!(dir.path(), std::path::Path::new(&format!("{root}/dir")));
+
+        let err = EzEntry::open(format!("{root}/missing")).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("file", format!("{root}/link")).unwrap();
+            assert!(EzEntry::open(format!("{root}/link")).unwrap().is_symlink());
+        }
+    }
+
+    #[test]
+    fn flatten_dirs_test() {
+        let root = scratch("flatten_dirs");
+        for d in ["a/b/c", "a/d", "e"] {
+            std::fs::create_dir_all(format!("{root}/{d}")).unwrap();
+        }
+        std::fs::write(format!("{root}/a/file"), "").unwrap();
+
+        let mut dir = EzDir::new(&root, false).unwrap();
+        dir.walk(0);
+        dir.sort_by_name();
+        dir[0].as_dir_mut().unwrap().sort_by_name();
+        let names = |paths: Vec<&std::path::Path>| -> Vec<String> {
+            paths.iter().map(|p| crate::dir::relative(std::path::Path::new(&root), p)).collect()
+        };
+        let expected = ["a", "a/b", "a/b/c", "a/d", "e"];
+        assert_eq!(names(dir.dirs().iter().map(|d| d.path()).collect()), expected);
+
+        let dirs = dir.flatten_dirs();
+        assert_eq!(dirs.len(), 5);
+        assert_eq!(names(dirs.iter().map(EzDir::path).collect()), expected);
+        //`a` keeps its file, but `b` and `d` are returned on their own
+        assert_eq!(dirs[0].len(), Some(3));
+        assert!(dirs[0].find("file").is_some());
+        assert!(dirs[0].find("c").is_none());
+        assert!(dirs[0].iter().filter_map(EzEntry::as_dir).all(|d| !d.is_cached()));
+    }
 }
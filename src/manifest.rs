@@ -0,0 +1,82 @@
+use crate::{dir::{relative, EzDir}, file::{hex, EzFile}, error::EzError};
+use std::{io::{self, BufRead, Write}, fs, path::Path, collections::BTreeMap};
+
+///A difference between a directory and a manifest written by [`EzDir::write_manifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestMismatch {
+    ///Listed in the manifest but missing from the directory.
+    Missing(String),
+    ///Present in the directory but not listed in the manifest.
+    Unlisted(String),
+    ///The file size differs from the manifest.
+    Size { path: String, expected: u64, actual: u64 },
+    ///The file contents differ from the manifest.
+    Hash(String),
+}
+
+impl EzDir {
+    ///Walks the whole directory and writes a manifest to `dest`, with a line per file holding its
+    ///SHA-256, size and path relative to this directory, sorted by path. `dest` should live
+    ///outside the directory, otherwise it will list itself. Returns a read-only handle to the
+    ///manifest.
+    ///```
+    ///use ez_fs::EzDir;
+    ///
+    ///let mut dir = EzDir::new("src", false).unwrap();
+    ///dir.write_manifest("foo_manifest.txt").unwrap();
+    ///assert!(dir.verify_manifest("foo_manifest.txt").unwrap().is_empty());
+    ///```
+    ///# Errors
+    ///This function will error if a file can't be read or `dest` can't be written.
+    pub fn write_manifest(&mut self, dest: impl AsRef<Path>) -> Result<EzFile, EzError> {
+        let dest = dest.as_ref();
+        let mut out = io::BufWriter::new(fs::File::create(dest)?);
+        for (path, (size, hash)) in self.hash_tree()? {
+            writeln!(out, "{} {size} {path}", hex(&hash))?;
+        }
+        out.flush()?;
+        drop(out);
+        EzFile::open(dest)
+    }
+
+    ///Walks the whole directory and checks it against a manifest written by
+    ///[`EzDir::write_manifest`], returning every difference found.
+    ///# Errors
+    ///This function will error if the manifest is malformed or a file can't be read.
+    pub fn verify_manifest(&mut self, manifest: impl AsRef<Path>) -> Result<Vec<ManifestMismatch>, EzError> {
+        let mut actual = self.hash_tree()?;
+        let mut mismatches = vec![];
+
+        for line in io::BufReader::new(fs::File::open(manifest)?).lines() {
+            let line = line?;
+            let mut parts = line.splitn(3, ' ');
+            let (Some(hash), Some(size), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Malformed manifest line: {line}")).into())
+            };
+            let expected: u64 = size.parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("Malformed manifest size: {size}")))?;
+
+            match actual.remove(path) {
+                None => mismatches.push(ManifestMismatch::Missing(path.to_string())),
+                Some((size, _)) if size != expected => mismatches.push(ManifestMismatch::Size { path: path.to_string(), expected, actual: size }),
+                Some((_, digest)) if hex(&digest) != hash => mismatches.push(ManifestMismatch::Hash(path.to_string())),
+                Some(_) => {},
+            }
+        }
+
+        mismatches.extend(actual.into_keys().map(ManifestMismatch::Unlisted));
+        Ok(mismatches)
+    }
+
+    //walks and hashes every file, keyed and sorted by relative path
+    fn hash_tree(&mut self) -> io::Result<BTreeMap<String, (u64, [u8; 32])>> {
+        self.try_walk(0)?;
+        let root = self.path().to_owned();
+        let mut map = BTreeMap::new();
+        for file in self.iter_files_mut() {
+            let size = fs::metadata(file.path())?.len();
+            map.insert(relative(&root, file.path()), (size, file.hash_sha256()?));
+        }
+        Ok(map)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{file::EzFile, error::EzError};
+use memmap2::Mmap;
+use std::{io, sync::Arc};
+
+impl EzFile {
+    ///Maps the file into memory and returns a reader over it. Cloning the reader is cheap and
+    ///every clone has its own cursor over the same map, so many threads can read one file
+    ///without their own handles or any locking.
+    ///
+    ///The file must not be changed or truncated while it's mapped, by this process or any other.
+    ///The map sees those changes directly, and truncating it can crash the program.
+    ///```
+    ///use ez_fs::EzFile;
+    ///use std::io::Read;
+    ///# std::fs::write("foo_mmap.txt", b"bar").unwrap();
+    ///
+    ///let file = EzFile::open("foo_mmap.txt").unwrap();
+    ///let reader = file.shared_reader().unwrap();
+    ///
+    ///let threads: Vec<_> = (0..4).map(|_| {
+    ///    let mut reader = reader.clone();
+    ///    std::thread::spawn(move || {
+    ///        let mut buf = String::new();
+    ///        reader.read_to_string(&mut buf).unwrap();
+    ///        buf
+    ///    })
+    ///}).collect();
+    ///for thread in threads {
+    ///    assert_eq!(thread.join().unwrap(), "bar");
+    ///}
+    ///```
+    ///# Errors
+    ///This function will error if the file can't be mapped.
+    pub fn shared_reader(&self) -> Result<SharedReader, EzError> {
+        //SAFETY: the caller is told not to modify the file while it is mapped
+        let map = unsafe { Mmap::map(self.handle())? };
+        Ok(SharedReader { map: Arc::new(map), pos: 0 })
+    }
+}
+
+///Reader over a memory mapped file, created by [`EzFile::shared_reader`].
+///Clones share the map but keep their own position.
+#[derive(Debug, Clone)]
+pub struct SharedReader {
+    map: Arc<Mmap>,
+    pos: u64,
+}
+impl SharedReader {
+    ///Returns the whole mapped file.
+    #[must_use] pub fn as_slice(&self) -> &[u8] {
+        &self.map
+    }
+
+    //unread part of the map
+    fn remaining(&self) -> &[u8] {
+        let start = usize::try_from(self.pos).map_or(self.map.len(), |pos| pos.min(self.map.len()));
+        &self.map[start..]
+    }
+}
+impl io::Read for SharedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = io::Read::read(&mut self.remaining(), buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+impl io::Seek for SharedReader {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            io::SeekFrom::Start(n) => {
+                self.pos = n;
+                return Ok(n)
+            },
+            io::SeekFrom::End(n) => (self.map.len() as u64, n),
+            io::SeekFrom::Current(n) => (self.pos, n),
+        };
+        self.pos = base.checked_add_signed(offset)
+            .ok_or(io::Error::new(io::ErrorKind::InvalidInput, "Invalid seek to a negative or overflowing position"))?;
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,73 @@
+//one-off operations that work on both files and directories, for when building an `EzFile` or
+//`EzDir` would be overkill
+use crate::{dir::copy_symlink, error::EzError};
+use std::{io, fs, path::Path};
+
+///Returns `true` if a file or directory exists at `path`.
+///```
+///assert!(ez_fs::exists("src"));
+///assert!(ez_fs::exists("Cargo.toml"));
+///```
+#[must_use] pub fn exists(path: impl AsRef<Path>) -> bool {
+    path.as_ref().exists()
+}
+
+///Removes a file, or a directory along with everything in it.
+///# Errors
+///This function will error if `path` does not exist or can't be removed.
+pub fn remove(path: impl AsRef<Path>) -> Result<(), EzError> {
+    let path = path.as_ref();
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+///Renames or moves a file or directory. Fails across filesystems.
+///# Errors
+///This function will error if `from` does not exist or can't be moved to `to`.
+pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<(), EzError> {
+    Ok(fs::rename(from, to)?)
+}
+
+///Copies a file, or a directory along with everything in it, creating `to` as needed. Symlinks
+///are recreated pointing at the same target rather than followed, so links to directories and
+///broken links are copied as they are.
+///```
+///ez_fs::copy("src", "foo_copied_src").unwrap();
+///assert!(ez_fs::exists("foo_copied_src/lib.rs"));
+///# ez_fs::remove("foo_copied_src").unwrap();
+///```
+///# Errors
+///This function will error if `from` does not exist or anything can't be copied.
+pub fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<(), EzError> {
+    fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            let dest = to.join(entry.file_name());
+            let file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                copy_symlink(&entry.path(), &dest)?;
+            } else if file_type.is_dir() {
+                copy_dir(&entry.path(), &dest)?;
+            } else {
+                fs::copy(entry.path(), dest)?;
+            }
+        }
+        Ok(())
+    }
+
+    let (from, to) = (from.as_ref(), to.as_ref());
+    let file_type = fs::symlink_metadata(from)?.file_type();
+    if file_type.is_symlink() {
+        copy_symlink(from, to)?;
+    } else if file_type.is_dir() {
+        copy_dir(from, to)?;
+    } else {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}